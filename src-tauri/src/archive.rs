@@ -0,0 +1,258 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use zip::ZipArchive;
+
+#[cfg(feature = "pdf")]
+use crate::pdf;
+use crate::{epub::read_epub_package, error::ArchiveError, fs::SUPPORTED_IMAGE_FORMATS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Epub,
+    #[cfg(feature = "cbr")]
+    Rar,
+    #[cfg(feature = "cb7")]
+    SevenZip,
+    #[cfg(feature = "pdf")]
+    Pdf,
+}
+
+impl ArchiveKind {
+    /// gets the archive kind from the path's extension, without touching the disk.
+    /// formats that weren't compiled in return `None`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "cbz" | "zip" => Some(Self::Zip),
+            "epub" => Some(Self::Epub),
+            #[cfg(feature = "cbr")]
+            "cbr" | "rar" => Some(Self::Rar),
+            #[cfg(feature = "cb7")]
+            "cb7" | "7z" => Some(Self::SevenZip),
+            #[cfg(feature = "pdf")]
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// checks if the path has a supported archive extension, without touching the disk
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+fn is_image_entry(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|ext| {
+            SUPPORTED_IMAGE_FORMATS
+                .get_key(&ext.to_string_lossy().to_lowercase())
+                .is_some()
+        })
+        .unwrap_or(false)
+}
+
+/// panels inside of an archive are addressed as if the archive was a folder:
+/// `C:/manga/Vol 1.cbz` + `001.jpg` -> `C:/manga/Vol 1.cbz/001.jpg`
+pub fn join_archive_path(archive: impl AsRef<Path>, entry: &str) -> String {
+    archive.as_ref().join(entry).to_string_lossy().to_string()
+}
+
+/// splits a virtual panel path into the archive on disk and the inner entry name.
+/// returns `None` if no ancestor of the path is an archive file.
+pub fn split_archive_path(path: impl AsRef<Path>) -> Option<(PathBuf, String)> {
+    let path = path.as_ref();
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|a| is_archive(a) && a.is_file())?;
+
+    // entry names are always stored with forward slashes
+    let entry = path
+        .strip_prefix(archive)
+        .ok()?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    Some((archive.to_path_buf(), entry))
+}
+
+/// lists the names of every entry inside the archive.
+/// epubs only list the images referenced by their spine, in reading order,
+/// and pdfs list one rendered image per page.
+pub fn archive_entry_names(archive: impl AsRef<Path>) -> Result<Vec<String>, ArchiveError> {
+    let archive = archive.as_ref();
+    let kind = ArchiveKind::from_path(archive)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(archive.to_string_lossy().to_string()))?;
+
+    let names = match kind {
+        ArchiveKind::Zip => zip_entry_names(archive)?,
+        ArchiveKind::Epub => read_epub_package(archive)?.pages,
+        #[cfg(feature = "cbr")]
+        ArchiveKind::Rar => rar::entry_names(archive)?,
+        #[cfg(feature = "cb7")]
+        ArchiveKind::SevenZip => seven_zip::entry_names(archive)?,
+        #[cfg(feature = "pdf")]
+        ArchiveKind::Pdf => pdf::page_entry_names(archive)?,
+    };
+
+    Ok(names)
+}
+
+/// lists every supported image inside the archive as a virtual panel path
+pub fn read_archive_panel_paths(archive: impl AsRef<Path>) -> Result<Vec<String>, ArchiveError> {
+    let archive = archive.as_ref();
+    Ok(entry_panel_paths(archive, &archive_entry_names(archive)?))
+}
+
+/// the supported images among the archive's entries as virtual panel paths, in the same order
+pub fn entry_panel_paths(archive: &Path, names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|name| !name.ends_with('/') && is_image_entry(name))
+        .map(|name| join_archive_path(archive, name))
+        .collect()
+}
+
+/// reads the raw bytes of a single entry
+pub fn read_archive_entry(archive: impl AsRef<Path>, entry: &str) -> Result<Vec<u8>, ArchiveError> {
+    let archive = archive.as_ref();
+    let kind = ArchiveKind::from_path(archive)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(archive.to_string_lossy().to_string()))?;
+
+    match kind {
+        ArchiveKind::Zip | ArchiveKind::Epub => read_zip_entry(archive, entry),
+        #[cfg(feature = "cbr")]
+        ArchiveKind::Rar => rar::read_entry(archive, entry),
+        #[cfg(feature = "cb7")]
+        ArchiveKind::SevenZip => seven_zip::read_entry(archive, entry),
+        #[cfg(feature = "pdf")]
+        ArchiveKind::Pdf => pdf::read_page(archive, entry),
+    }
+}
+
+/// reads up to `len` bytes from the start of an entry, for when only its header is needed.
+/// formats that can't stream a single entry read all of it.
+pub fn read_archive_entry_head(
+    archive: impl AsRef<Path>,
+    entry: &str,
+    len: u64,
+) -> Result<Vec<u8>, ArchiveError> {
+    let archive = archive.as_ref();
+    match ArchiveKind::from_path(archive) {
+        Some(ArchiveKind::Zip | ArchiveKind::Epub) => {
+            let mut zip = open_zip(archive)?;
+            let file = zip.by_name(entry)?;
+
+            let mut buf = Vec::with_capacity(file.size().min(len) as usize);
+            file.take(len).read_to_end(&mut buf)?;
+            Ok(buf)
+        }
+        _ => read_archive_entry(archive, entry),
+    }
+}
+
+// zip
+
+pub(crate) fn open_zip(archive: &Path) -> Result<ZipArchive<BufReader<File>>, ArchiveError> {
+    let file = File::open(archive)?;
+    Ok(ZipArchive::new(BufReader::new(file))?)
+}
+
+fn zip_entry_names(archive: &Path) -> Result<Vec<String>, ArchiveError> {
+    let zip = open_zip(archive)?;
+    Ok(zip.file_names().map(str::to_string).collect())
+}
+
+fn read_zip_entry(archive: &Path, entry: &str) -> Result<Vec<u8>, ArchiveError> {
+    let mut zip = open_zip(archive)?;
+    let mut file = zip.by_name(entry)?;
+
+    let mut buf = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut buf)?;
+
+    Ok(buf)
+}
+
+#[cfg(feature = "cbr")]
+mod rar {
+    use std::path::Path;
+
+    use unrar::Archive;
+
+    use crate::error::ArchiveError;
+
+    pub fn entry_names(archive: &Path) -> Result<Vec<String>, ArchiveError> {
+        let mut names = Vec::new();
+        for header in Archive::new(archive).open_for_listing()? {
+            let header = header?;
+            if header.is_file() {
+                names.push(header.filename.to_string_lossy().replace('\\', "/"));
+            }
+        }
+        Ok(names)
+    }
+
+    pub fn read_entry(archive: &Path, entry: &str) -> Result<Vec<u8>, ArchiveError> {
+        let mut open = Archive::new(archive).open_for_processing()?;
+        while let Some(header) = open.read_header()? {
+            let name = header.entry().filename.to_string_lossy().replace('\\', "/");
+            if name == entry {
+                let (data, _) = header.read()?;
+                return Ok(data);
+            }
+            open = header.skip()?;
+        }
+
+        Err(ArchiveError::EntryNotFound(entry.to_string()))
+    }
+}
+
+#[cfg(feature = "cb7")]
+mod seven_zip {
+    use std::{io, path::Path};
+
+    use sevenz_rust::{Password, SevenZReader};
+
+    use crate::error::ArchiveError;
+
+    pub fn entry_names(archive: &Path) -> Result<Vec<String>, ArchiveError> {
+        let reader = SevenZReader::open(archive, Password::empty())?;
+        let names = reader
+            .archive()
+            .files
+            .iter()
+            .filter(|e| !e.is_directory())
+            .map(|e| e.name().replace('\\', "/"))
+            .collect();
+        Ok(names)
+    }
+
+    pub fn read_entry(archive: &Path, entry: &str) -> Result<Vec<u8>, ArchiveError> {
+        let mut reader = SevenZReader::open(archive, Password::empty())?;
+        let mut data = None;
+
+        // solid blocks have to be decoded in order, so every entry
+        // in front of the one we want still has to be drained
+        reader.for_each_entries(|e, r| {
+            if data.is_some() {
+                return Ok(false);
+            }
+            if e.name().replace('\\', "/") == entry {
+                let mut buf = Vec::with_capacity(e.size() as usize);
+                r.read_to_end(&mut buf)?;
+                data = Some(buf);
+                return Ok(false);
+            }
+            io::copy(r, &mut io::sink())?;
+            Ok(true)
+        })?;
+
+        data.ok_or_else(|| ArchiveError::EntryNotFound(entry.to_string()))
+    }
+}
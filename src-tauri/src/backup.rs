@@ -0,0 +1,582 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use native_db::{transaction::RwTransaction, ToInput, ToKey};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+
+use crate::{
+    crop::DEFAULT_CROP_TOLERANCE,
+    database::{
+        data::v2::{
+            ChapterCrop, CoverRef, EpubSpine, FolderCrop, FolderReadingProfile, LibraryRoot,
+            MangaPanel, OsFolder, PanelDimensions, SeriesMetadata, Settings, SplitPanel,
+            SplitSpreads, User, WebtoonStrip,
+        },
+        get_root_os_folders, rebuild_cover_refs, remove_split_files, remove_webtoon_dirs,
+        ClockFormat, FileMetadata, FolderMetadata, HasTimestamp, ReadingProfile,
+    },
+    error::BackupError,
+    misc::now_timestamp,
+    repository::RepositoryExt,
+    watcher::{unwatch_library_root, watch_library_root},
+};
+
+/// a snapshot is taken once the newest one is this old
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// how often the schedule checks whether a snapshot is due
+const SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// the oldest snapshots are deleted once there are more than this
+const MAX_SNAPSHOTS: usize = 7;
+
+/// what every export starts with, so other JSON files are turned down
+const EXPORT_FORMAT: &str = "mangashelf-library";
+
+/// bumped whenever the shape of `LibraryExport` changes.
+/// 2 added `auto_crop`, `crop_tolerance` and `reading_profile` to the settings.
+/// 3 added the `page_count` of pdfs to the folder metadata.
+const EXPORT_VERSION: u32 = 3;
+
+/// a snapshot that's restored the next time the app starts, while the database is closed
+const PENDING_RESTORE: &str = "main.restore.rdb";
+
+pub fn snapshot_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("backups")
+}
+
+/// a copy of the whole database, `app_data/backups/main.1732968900000.rdb`
+#[derive(Serialize, Clone, Debug)]
+pub struct DatabaseSnapshot {
+    pub path: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+impl DatabaseSnapshot {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let created_at = name
+            .strip_prefix("main.")?
+            .strip_suffix(".rdb")?
+            .parse()
+            .ok()?;
+        Some(DatabaseSnapshot {
+            path: path.to_string_lossy().to_string(),
+            created_at,
+            size: path.metadata().ok()?.len(),
+        })
+    }
+}
+
+/// newest first
+fn list_snapshots(app_data_dir: &Path) -> io::Result<Vec<DatabaseSnapshot>> {
+    let dir = snapshot_dir(app_data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots: Vec<DatabaseSnapshot> = fs::read_dir(dir)?
+        .filter_map(|entry| DatabaseSnapshot::from_path(&entry.ok()?.path()))
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    Ok(snapshots)
+}
+
+/// snapshots the database and deletes the ones past `MAX_SNAPSHOTS`
+fn take_snapshot(handle: &AppHandle) -> Result<DatabaseSnapshot, BackupError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    let dir = snapshot_dir(&app_data_dir);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!("main.{}.rdb", now_timestamp()));
+    handle.repo().snapshot(&path)?;
+
+    for old in list_snapshots(&app_data_dir)?.iter().skip(MAX_SNAPSHOTS) {
+        if let Err(e) = fs::remove_file(&old.path) {
+            eprintln!("failed to delete snapshot {}: {e}", old.path);
+        }
+    }
+
+    DatabaseSnapshot::from_path(&path)
+        .ok_or_else(|| BackupError::SnapshotNotFound(path.to_string_lossy().to_string()))
+}
+
+fn snapshot_if_due(handle: &AppHandle) -> Result<(), BackupError> {
+    let snapshots = list_snapshots(&handle.path().app_data_dir()?)?;
+    let is_due = snapshots.first().is_none_or(|newest| {
+        now_timestamp() - newest.created_at >= SNAPSHOT_INTERVAL.as_millis() as i64
+    });
+    if is_due {
+        take_snapshot(handle)?;
+    }
+    Ok(())
+}
+
+/// takes a snapshot on startup and then once a day, if the newest one is old enough.
+/// the app still works without them, so errors are only logged.
+pub fn init_snapshot_schedule(handle: &AppHandle) {
+    let handle = handle.clone();
+    thread::spawn(move || loop {
+        if let Err(e) = snapshot_if_due(&handle) {
+            eprintln!("failed to snapshot the database: {e}");
+        }
+        thread::sleep(SNAPSHOT_CHECK_INTERVAL);
+    });
+}
+
+/// moves a snapshot staged by `restore_database_snapshot` in place of the database.
+/// called before the database is opened.
+pub fn apply_pending_restore(app_data_dir: &Path, db_path: &Path) -> io::Result<()> {
+    let pending = app_data_dir.join(PENDING_RESTORE);
+    if pending.exists() {
+        fs::rename(pending, db_path)?;
+    }
+    Ok(())
+}
+
+#[command]
+pub fn create_database_snapshot(handle: AppHandle) -> Result<DatabaseSnapshot, BackupError> {
+    take_snapshot(&handle)
+}
+
+#[command]
+pub fn get_database_snapshots(handle: AppHandle) -> Result<Vec<DatabaseSnapshot>, BackupError> {
+    Ok(list_snapshots(&handle.path().app_data_dir()?)?)
+}
+
+/// replaces the database with a snapshot and restarts the app to load it.
+/// the current database is snapshotted first, so the restore can be undone.
+#[command]
+pub fn restore_database_snapshot(handle: AppHandle, path: String) -> Result<(), BackupError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    let snapshot = list_snapshots(&app_data_dir)?
+        .into_iter()
+        .find(|s| s.path == path)
+        .ok_or(BackupError::SnapshotNotFound(path))?;
+
+    // staged before the safety snapshot, which could rotate the chosen one away
+    let pending = app_data_dir.join(PENDING_RESTORE);
+    fs::copy(&snapshot.path, &pending)?;
+    if let Err(e) = take_snapshot(&handle) {
+        fs::remove_file(&pending).ok();
+        return Err(e);
+    }
+    handle.restart()
+}
+
+/// the users, folders and panels of a library, so reading progress can move between machines.
+/// the file looks like:
+///
+/// ```json
+/// {
+///   "format": "mangashelf-library",
+///   "version": 2,
+///   "exported_at": 1732968900000,
+///   "users": [{ "id": "1", "username": "default", "settings": { ... }, ... }],
+///   "folders": [{ "user_id": "1", "path": "/manga/Berserk", "is_read": false, ... }],
+///   "panels": [{ "user_id": "1", "path": "/manga/Berserk/001.jpg", "is_read": true, ... }]
+/// }
+/// ```
+///
+/// the records are their own types, so the database models can change without changing the file.
+/// timestamps are UTC milliseconds and a user's settings are part of the user.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LibraryExport {
+    /// always `mangashelf-library`
+    pub format: String,
+    /// files from a newer version than `EXPORT_VERSION` are turned down
+    pub version: u32,
+    pub exported_at: i64,
+    pub users: Vec<ExportUser>,
+    pub folders: Vec<ExportFolder>,
+    pub panels: Vec<ExportPanel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportUser {
+    pub id: String,
+    pub username: String,
+    pub settings: ExportSettings,
+    pub last_read_manga_folder: Option<ExportFolder>,
+}
+
+fn default_crop_tolerance() -> u8 {
+    DEFAULT_CROP_TOLERANCE
+}
+
+/// the fields added in version 2 fall back to the defaults for version 1 files
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportSettings {
+    pub user_id: String,
+    pub mpv_path: Option<String>,
+    pub plugins_path: Option<String>,
+    pub autoplay: bool,
+    pub clock: ClockFormat,
+    #[serde(default)]
+    pub auto_crop: bool,
+    #[serde(default = "default_crop_tolerance")]
+    pub crop_tolerance: u8,
+    #[serde(default)]
+    pub reading_profile: ReadingProfile,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportFolder {
+    pub user_id: String,
+    pub path: String,
+    pub title: String,
+    pub parent_path: Option<String>,
+    pub last_read_panel: Option<ExportPanel>,
+    pub cover_img_path: Option<String>,
+    pub metadata: Option<FolderMetadata>,
+    pub is_manga_folder: bool,
+    pub is_double_panels: bool,
+    pub is_read: bool,
+    pub zoom: usize,
+    pub is_hidden: bool,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportPanel {
+    pub user_id: String,
+    pub path: String,
+    pub title: String,
+    pub parent_path: String,
+    pub metadata: Option<FileMetadata>,
+    pub is_read: bool,
+    pub updated_at: i64,
+}
+
+impl From<User> for ExportUser {
+    fn from(user: User) -> Self {
+        ExportUser {
+            id: user.id,
+            username: user.username,
+            settings: user.settings.into(),
+            last_read_manga_folder: user.last_read_manga_folder.map(Into::into),
+        }
+    }
+}
+
+impl From<ExportUser> for User {
+    fn from(user: ExportUser) -> Self {
+        User {
+            id: user.id,
+            username: user.username,
+            settings: user.settings.into(),
+            last_read_manga_folder: user.last_read_manga_folder.map(Into::into),
+        }
+    }
+}
+
+impl From<Settings> for ExportSettings {
+    fn from(settings: Settings) -> Self {
+        ExportSettings {
+            user_id: settings.user_id,
+            mpv_path: settings.mpv_path,
+            plugins_path: settings.plugins_path,
+            autoplay: settings.autoplay,
+            clock: settings.clock,
+            auto_crop: settings.auto_crop,
+            crop_tolerance: settings.crop_tolerance,
+            reading_profile: settings.reading_profile,
+            updated_at: settings.updated_at,
+        }
+    }
+}
+
+impl From<ExportSettings> for Settings {
+    fn from(settings: ExportSettings) -> Self {
+        Settings {
+            user_id: settings.user_id,
+            mpv_path: settings.mpv_path,
+            plugins_path: settings.plugins_path,
+            autoplay: settings.autoplay,
+            clock: settings.clock,
+            auto_crop: settings.auto_crop,
+            crop_tolerance: settings.crop_tolerance,
+            reading_profile: settings.reading_profile,
+            updated_at: settings.updated_at,
+        }
+    }
+}
+
+impl From<OsFolder> for ExportFolder {
+    fn from(folder: OsFolder) -> Self {
+        ExportFolder {
+            user_id: folder.user_id,
+            path: folder.path,
+            title: folder.title,
+            parent_path: folder.parent_path,
+            last_read_panel: folder.last_read_panel.map(Into::into),
+            cover_img_path: folder.cover_img_path,
+            metadata: folder.metadata,
+            is_manga_folder: folder.is_manga_folder,
+            is_double_panels: folder.is_double_panels,
+            is_read: folder.is_read,
+            zoom: folder.zoom,
+            is_hidden: folder.is_hidden,
+            updated_at: folder.updated_at,
+        }
+    }
+}
+
+impl From<ExportFolder> for OsFolder {
+    fn from(folder: ExportFolder) -> Self {
+        OsFolder {
+            user_id: folder.user_id,
+            path: folder.path,
+            title: folder.title,
+            parent_path: folder.parent_path,
+            last_read_panel: folder.last_read_panel.map(Into::into),
+            cover_img_path: folder.cover_img_path,
+            metadata: folder.metadata,
+            is_manga_folder: folder.is_manga_folder,
+            is_double_panels: folder.is_double_panels,
+            is_read: folder.is_read,
+            zoom: folder.zoom,
+            is_hidden: folder.is_hidden,
+            updated_at: folder.updated_at,
+        }
+    }
+}
+
+impl From<MangaPanel> for ExportPanel {
+    fn from(panel: MangaPanel) -> Self {
+        ExportPanel {
+            user_id: panel.user_id,
+            path: panel.path,
+            title: panel.title,
+            parent_path: panel.parent_path,
+            metadata: panel.metadata,
+            is_read: panel.is_read,
+            updated_at: panel.updated_at,
+        }
+    }
+}
+
+impl From<ExportPanel> for MangaPanel {
+    fn from(panel: ExportPanel) -> Self {
+        MangaPanel {
+            user_id: panel.user_id,
+            path: panel.path,
+            title: panel.title,
+            parent_path: panel.parent_path,
+            metadata: panel.metadata,
+            is_read: panel.is_read,
+            updated_at: panel.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum ImportStrategy {
+    /// keeps everything that's only in the database,
+    /// a record in both keeps whichever was updated last
+    Merge,
+    /// deletes every user, folder and panel before importing,
+    /// along with everything that's kept per folder
+    Replace,
+}
+
+/// how many of each record were written
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct ImportSummary {
+    pub users: usize,
+    pub folders: usize,
+    pub panels: usize,
+}
+
+fn read_library(handle: &AppHandle) -> Result<LibraryExport, BackupError> {
+    let db = handle.repo();
+    let rtx = db.read()?;
+    let users: Vec<User> = rtx.scan().primary()?.all()?.try_collect()?;
+    let folders: Vec<OsFolder> = rtx.scan().primary()?.all()?.try_collect()?;
+    let panels: Vec<MangaPanel> = rtx.scan().primary()?.all()?.try_collect()?;
+
+    Ok(LibraryExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: now_timestamp(),
+        users: users.into_iter().map(Into::into).collect(),
+        folders: folders.into_iter().map(Into::into).collect(),
+        panels: panels.into_iter().map(Into::into).collect(),
+    })
+}
+
+fn read_export(path: &Path) -> Result<LibraryExport, BackupError> {
+    let export: LibraryExport = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    if export.format != EXPORT_FORMAT {
+        return Err(BackupError::UnsupportedExport(format!(
+            "{} isn't a mangashelf library export",
+            path.display()
+        )));
+    }
+    if export.version > EXPORT_VERSION {
+        return Err(BackupError::UnsupportedExport(format!(
+            "{} is export version {}, this version of mangashelf reads up to {EXPORT_VERSION}",
+            path.display(),
+            export.version
+        )));
+    }
+    Ok(export)
+}
+
+/// upserts the records that aren't in the database or were updated after the ones that are
+fn merge_records<T, K>(
+    rwtx: &RwTransaction,
+    records: Vec<T>,
+    key: impl Fn(&T) -> K,
+    updated_at: impl Fn(&T) -> i64,
+) -> Result<usize, BackupError>
+where
+    T: ToInput,
+    K: ToKey,
+{
+    let mut written = 0;
+    for record in records {
+        let existing: Option<T> = rwtx.get().primary(key(&record))?;
+        if existing.is_none_or(|e| updated_at(&e) < updated_at(&record)) {
+            rwtx.upsert(record)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// returns the removed records
+fn remove_all<T: ToInput>(rwtx: &RwTransaction) -> Result<Vec<T>, BackupError> {
+    let records: Vec<T> = rwtx.scan().primary()?.all()?.try_collect()?;
+    Ok(records
+        .into_iter()
+        .map(|record| rwtx.remove(record))
+        .try_collect()?)
+}
+
+/// the cached files of split panels and webtoon tiles left behind by the removed rows
+#[derive(Default)]
+struct UnusedFiles {
+    splits: Vec<String>,
+    strips: Vec<String>,
+}
+
+/// empties every table that's kept per folder, so none of it outlives the replaced library.
+/// cover refs are recounted from the imported folders afterwards.
+fn remove_library(rwtx: &RwTransaction) -> Result<UnusedFiles, BackupError> {
+    remove_all::<User>(rwtx)?;
+    remove_all::<OsFolder>(rwtx)?;
+    remove_all::<MangaPanel>(rwtx)?;
+    remove_all::<LibraryRoot>(rwtx)?;
+    remove_all::<CoverRef>(rwtx)?;
+    remove_all::<PanelDimensions>(rwtx)?;
+    remove_all::<SeriesMetadata>(rwtx)?;
+    remove_all::<SplitSpreads>(rwtx)?;
+    remove_all::<FolderCrop>(rwtx)?;
+    remove_all::<ChapterCrop>(rwtx)?;
+    remove_all::<FolderReadingProfile>(rwtx)?;
+    remove_all::<EpubSpine>(rwtx)?;
+
+    Ok(UnusedFiles {
+        splits: remove_all::<SplitPanel>(rwtx)?
+            .into_iter()
+            .flat_map(|s| [s.left, s.right])
+            .collect(),
+        strips: remove_all::<WebtoonStrip>(rwtx)?
+            .into_iter()
+            .map(|s| s.dir)
+            .collect(),
+    })
+}
+
+fn write_library(
+    handle: &AppHandle,
+    export: LibraryExport,
+    strategy: ImportStrategy,
+) -> Result<ImportSummary, BackupError> {
+    let old_roots = get_root_os_folders(handle)?;
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    let unused = match strategy {
+        ImportStrategy::Merge => UnusedFiles::default(),
+        ImportStrategy::Replace => remove_library(&rwtx)?,
+    };
+
+    let summary = ImportSummary {
+        users: merge_records(
+            &rwtx,
+            export.users.into_iter().map(User::from).collect(),
+            |u| u.id.clone(),
+            HasTimestamp::updated_at,
+        )?,
+        folders: merge_records(
+            &rwtx,
+            export.folders.into_iter().map(OsFolder::from).collect(),
+            |f| f.path.clone(),
+            HasTimestamp::updated_at,
+        )?,
+        panels: merge_records(
+            &rwtx,
+            export.panels.into_iter().map(MangaPanel::from).collect(),
+            |p| p.path.clone(),
+            HasTimestamp::updated_at,
+        )?,
+    };
+
+    rwtx.commit()?;
+
+    remove_split_files(unused.splits);
+    remove_webtoon_dirs(unused.strips);
+    rebuild_cover_refs(handle)?;
+
+    // the watcher follows the roots of the imported library
+    let new_roots: Vec<String> = get_root_os_folders(handle)?
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    for root in old_roots.iter().filter(|r| !new_roots.contains(&r.path)) {
+        unwatch_library_root(handle, &root.path);
+    }
+    for root in &new_roots {
+        watch_library_root(handle, root);
+    }
+
+    Ok(summary)
+}
+
+/// writes every user, folder and panel to a `LibraryExport` JSON file
+#[command]
+pub async fn export_library(handle: AppHandle, path: String) -> Result<(), BackupError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), BackupError> {
+        let export = read_library(&handle)?;
+        let writer = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(writer, &export)?;
+        Ok(())
+    })
+    .await?
+}
+
+/// reads a `LibraryExport` JSON file into the database.
+/// the database is snapshotted first, so an import can be undone with `restore_database_snapshot`.
+#[command]
+pub async fn import_library(
+    handle: AppHandle,
+    path: String,
+    strategy: ImportStrategy,
+) -> Result<ImportSummary, BackupError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<ImportSummary, BackupError> {
+        let export = read_export(Path::new(&path))?;
+        take_snapshot(&handle)?;
+        write_library(&handle, export, strategy)
+    })
+    .await?
+}
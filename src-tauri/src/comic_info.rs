@@ -0,0 +1,488 @@
+use std::{
+    fs::{self, read_dir, File},
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use quick_xml::{events::Event, Reader, Writer};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    archive::{archive_entry_names, is_archive, open_zip, read_archive_entry, ArchiveKind},
+    database::{data::v2::SeriesMetadata, update_series_metadata, ComicPageInfo, MangaReadingFlag},
+    error::{ComicInfoError, MangaShelfError, ReadDirError},
+};
+
+pub const COMIC_INFO_FILE_NAME: &str = "ComicInfo.xml";
+
+/// the elements that get replaced when writing a ComicInfo.xml back out.
+/// everything else in an existing file is kept as is.
+const MANAGED_ELEMENTS: &[&[u8]] = &[
+    b"Title",
+    b"Series",
+    b"Number",
+    b"Volume",
+    b"Summary",
+    b"Writer",
+    b"Penciller",
+    b"Genre",
+    b"LanguageISO",
+    b"Manga",
+    b"Pages",
+];
+
+/// the subset of the anansi `ComicInfo.xml` schema that mangashelf understands
+/// see: https://anansi-project.github.io/docs/comicinfo/documentation
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename = "ComicInfo")]
+pub struct ComicInfo {
+    #[serde(rename = "Title", skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "Series", skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(rename = "Number", skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    #[serde(rename = "Volume", skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    #[serde(rename = "Summary", skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(rename = "Writer", skip_serializing_if = "Option::is_none")]
+    pub writer: Option<String>,
+    #[serde(rename = "Penciller", skip_serializing_if = "Option::is_none")]
+    pub penciller: Option<String>,
+    #[serde(rename = "Genre", skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    #[serde(rename = "LanguageISO", skip_serializing_if = "Option::is_none")]
+    pub language_iso: Option<String>,
+    #[serde(rename = "Manga", skip_serializing_if = "Option::is_none")]
+    pub manga: Option<String>,
+    #[serde(rename = "Pages", skip_serializing_if = "Option::is_none")]
+    pub pages: Option<ComicInfoPages>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ComicInfoPages {
+    #[serde(rename = "Page", default)]
+    pub page: Vec<ComicInfoPage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ComicInfoPage {
+    #[serde(rename = "@Image")]
+    pub image: u32,
+    #[serde(rename = "@Type", skip_serializing_if = "Option::is_none")]
+    pub page_type: Option<String>,
+    #[serde(rename = "@DoublePage", skip_serializing_if = "Option::is_none")]
+    pub double_page: Option<String>,
+}
+
+/// ComicInfo stores lists as comma separated strings
+fn split_list(list: Option<String>) -> Vec<String> {
+    list.map(|l| {
+        l.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn parse_bool(b: &str) -> bool {
+    b.eq_ignore_ascii_case("true") || b == "1"
+}
+
+impl ComicInfo {
+    pub fn from_xml(xml: &str) -> Result<Self, ReadDirError> {
+        Ok(quick_xml::de::from_str(xml)?)
+    }
+
+    pub fn into_series_metadata(self, user_id: String, folder_path: String) -> SeriesMetadata {
+        let manga = match self.manga.as_deref() {
+            Some("Yes") => MangaReadingFlag::Yes,
+            Some("YesAndRightToLeft") => MangaReadingFlag::YesAndRightToLeft,
+            Some("No") => MangaReadingFlag::No,
+            _ => MangaReadingFlag::Unknown,
+        };
+
+        let pages = self
+            .pages
+            .map(|p| p.page)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| ComicPageInfo {
+                image: p.image,
+                page_type: p.page_type,
+                is_double_page: p.double_page.as_deref().map(parse_bool).unwrap_or(false),
+            })
+            .collect();
+
+        SeriesMetadata {
+            folder_path,
+            user_id,
+            title: self.title,
+            series: self.series,
+            volume: self.volume.and_then(|v| v.trim().parse().ok()),
+            number: self.number,
+            writers: split_list(self.writer),
+            pencillers: split_list(self.penciller),
+            summary: self.summary,
+            genres: split_list(self.genre),
+            language: self.language_iso,
+            manga,
+            pages,
+        }
+    }
+}
+
+impl From<&SeriesMetadata> for ComicInfo {
+    fn from(metadata: &SeriesMetadata) -> Self {
+        let join_list = |list: &[String]| (!list.is_empty()).then(|| list.join(", "));
+
+        let manga = match metadata.manga {
+            MangaReadingFlag::Unknown => None,
+            MangaReadingFlag::No => Some("No"),
+            MangaReadingFlag::Yes => Some("Yes"),
+            MangaReadingFlag::YesAndRightToLeft => Some("YesAndRightToLeft"),
+        };
+
+        let pages = (!metadata.pages.is_empty()).then(|| ComicInfoPages {
+            page: metadata
+                .pages
+                .iter()
+                .map(|p| ComicInfoPage {
+                    image: p.image,
+                    page_type: p.page_type.clone(),
+                    double_page: p.is_double_page.then(|| String::from("True")),
+                })
+                .collect(),
+        });
+
+        ComicInfo {
+            title: metadata.title.clone(),
+            series: metadata.series.clone(),
+            number: metadata.number.clone(),
+            volume: metadata.volume.map(|v| v.to_string()),
+            summary: metadata.summary.clone(),
+            writer: join_list(&metadata.writers),
+            penciller: join_list(&metadata.pencillers),
+            genre: join_list(&metadata.genres),
+            language_iso: metadata.language.clone(),
+            manga: manga.map(str::to_string),
+            pages,
+        }
+    }
+}
+
+/// looks for a `ComicInfo.xml` at the root of a folder or archive.
+/// the file name is matched case-insensitively, since taggers don't agree on it.
+pub fn read_comic_info(path: impl AsRef<Path>) -> Result<Option<ComicInfo>, ReadDirError> {
+    let path = path.as_ref();
+    let is_comic_info = |name: &str| name.eq_ignore_ascii_case(COMIC_INFO_FILE_NAME);
+
+    let xml = if is_archive(path) {
+        let Some(entry) = archive_entry_names(path)?
+            .into_iter()
+            .find(|name| is_comic_info(name))
+        else {
+            return Ok(None);
+        };
+        let bytes = read_archive_entry(path, &entry)?;
+        String::from_utf8_lossy(&bytes).into_owned()
+    } else {
+        let Some(entry) = read_dir(path)?
+            .flatten()
+            .find(|e| is_comic_info(&e.file_name().to_string_lossy()))
+        else {
+            return Ok(None);
+        };
+        std::fs::read_to_string(entry.path())?
+    };
+
+    Ok(Some(ComicInfo::from_xml(&xml)?))
+}
+
+/// serializes `info` into an existing ComicInfo.xml, replacing only the elements
+/// mangashelf manages, so fields written by other taggers survive the round trip.
+fn merge_comic_info_xml(
+    existing: Option<&str>,
+    info: &ComicInfo,
+) -> Result<String, ComicInfoError> {
+    let fresh = quick_xml::se::to_string(info)?;
+    let Some(existing) = existing else {
+        return Ok(format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{fresh}"
+        ));
+    };
+
+    let inner = fresh
+        .strip_prefix("<ComicInfo>")
+        .and_then(|f| f.strip_suffix("</ComicInfo>"))
+        .unwrap_or_default();
+
+    let mut reader = Reader::from_str(existing);
+    let mut writer = Writer::new(Vec::new());
+    let mut depth = 0;
+    let mut skip_depth: Option<usize> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) => {
+                depth += 1;
+                if skip_depth.is_none()
+                    && depth == 2
+                    && MANAGED_ELEMENTS.contains(&e.name().as_ref())
+                {
+                    skip_depth = Some(depth);
+                }
+            }
+            Event::Empty(e) if depth == 0 && e.name().as_ref() == b"ComicInfo" => {
+                // `<ComicInfo/>` has nothing to keep
+                writer.write_event(Event::Start(e.to_owned()))?;
+                writer.get_mut().extend_from_slice(inner.as_bytes());
+                writer.write_event(Event::End(e.to_end().into_owned()))?;
+                continue;
+            }
+            Event::Empty(e) if depth == 1 && MANAGED_ELEMENTS.contains(&e.name().as_ref()) => {
+                continue;
+            }
+            Event::End(e) => {
+                if skip_depth == Some(depth) {
+                    skip_depth = None;
+                    depth -= 1;
+                    continue;
+                }
+                if depth == 1 && e.name().as_ref() == b"ComicInfo" {
+                    writer.get_mut().extend_from_slice(inner.as_bytes());
+                }
+                depth -= 1;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        if skip_depth.is_none() {
+            writer.write_event(event)?;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+/// writes the file next to the destination, for `replace_with_tmp` to move over it.
+/// it's removed again if writing fails.
+fn write_tmp(
+    dest: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), ComicInfoError>,
+) -> Result<PathBuf, ComicInfoError> {
+    let mut tmp_name = dest.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = dest.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut buf = BufWriter::new(File::create(&tmp)?);
+        write(&mut buf)?;
+        buf.flush()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => Ok(tmp),
+        Err(e) => {
+            fs::remove_file(&tmp).ok();
+            Err(e)
+        }
+    }
+}
+
+/// windows can't rename over a file that's still open, so nothing may hold `dest` by now
+fn replace_with_tmp(tmp: &Path, dest: &Path) -> Result<(), ComicInfoError> {
+    fs::rename(tmp, dest).map_err(|e| {
+        fs::remove_file(tmp).ok();
+        e.into()
+    })
+}
+
+/// writes the file next to the destination first, then renames it over the old one,
+/// so a crash mid-write never leaves a half written file behind
+fn write_atomically(
+    dest: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), ComicInfoError>,
+) -> Result<(), ComicInfoError> {
+    let tmp = write_tmp(dest, write)?;
+    replace_with_tmp(&tmp, dest)
+}
+
+fn write_folder_comic_info(folder: &Path, info: &ComicInfo) -> Result<(), ComicInfoError> {
+    // keep whatever casing the existing file uses
+    let dest = read_dir(folder)?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            p.file_name()
+                .is_some_and(|n| n.eq_ignore_ascii_case(COMIC_INFO_FILE_NAME))
+        })
+        .unwrap_or_else(|| folder.join(COMIC_INFO_FILE_NAME));
+
+    let existing = fs::read_to_string(&dest).ok();
+    let xml = merge_comic_info_xml(existing.as_deref(), info)?;
+
+    write_atomically(&dest, |buf| Ok(buf.write_all(xml.as_bytes())?))
+}
+
+/// rebuilds the archive with the new ComicInfo.xml.
+/// the other entries are copied over without being recompressed.
+fn write_archive_comic_info(archive: &Path, info: &ComicInfo) -> Result<(), ComicInfoError> {
+    if ArchiveKind::from_path(archive) != Some(ArchiveKind::Zip) {
+        return Err(ComicInfoError::ReadOnlyArchive(
+            archive.to_string_lossy().to_string(),
+        ));
+    }
+
+    let mut zip = open_zip(archive)?;
+    let existing_name = zip
+        .file_names()
+        .find(|n| n.eq_ignore_ascii_case(COMIC_INFO_FILE_NAME))
+        .map(str::to_string);
+
+    let existing = match &existing_name {
+        Some(name) => {
+            let mut xml = Vec::new();
+            zip.by_name(name)?.read_to_end(&mut xml)?;
+            Some(String::from_utf8_lossy(&xml).into_owned())
+        }
+        None => None,
+    };
+    let xml = merge_comic_info_xml(existing.as_deref(), info)?;
+
+    let tmp = write_tmp(archive, |buf| {
+        let mut writer = ZipWriter::new(buf);
+        for i in 0..zip.len() {
+            let file = zip.by_index(i)?;
+            if Some(file.name()) == existing_name.as_deref() {
+                continue;
+            }
+            writer.raw_copy_file(file)?;
+        }
+
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file(
+            existing_name.as_deref().unwrap_or(COMIC_INFO_FILE_NAME),
+            options,
+        )?;
+        writer.write_all(xml.as_bytes())?;
+        writer.finish()?;
+        Ok(())
+    })?;
+
+    // the archive is closed before the rebuilt one replaces it
+    drop(zip);
+    replace_with_tmp(&tmp, archive)
+}
+
+/// writes the metadata to the ComicInfo.xml of the folder or cbz it belongs to
+pub fn write_comic_info(metadata: &SeriesMetadata) -> Result<(), ComicInfoError> {
+    let path = PathBuf::from(&metadata.folder_path);
+    let info = ComicInfo::from(metadata);
+
+    if is_archive(&path) {
+        write_archive_comic_info(&path, &info)
+    } else if path.is_dir() {
+        write_folder_comic_info(&path, &info)
+    } else {
+        Err(ComicInfoError::Path(metadata.folder_path.clone()))
+    }
+}
+
+/// saves edited series metadata and writes it back out to the ComicInfo.xml,
+/// so other readers (Komga, Kavita, KOReader) see the same data
+/// rewriting an archive copies all of it, so it runs off the main thread
+#[command]
+pub async fn save_series_metadata(
+    handle: AppHandle,
+    metadata: SeriesMetadata,
+) -> Result<(), MangaShelfError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), MangaShelfError> {
+        write_comic_info(&metadata)?;
+        update_series_metadata(&handle, vec![metadata])?;
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> ComicInfo {
+        ComicInfo {
+            title: Some("Vol 2".to_string()),
+            series: Some("Berserk".to_string()),
+            pages: Some(ComicInfoPages {
+                page: vec![ComicInfoPage {
+                    image: 0,
+                    page_type: Some("FrontCover".to_string()),
+                    double_page: None,
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn writes_a_new_file() {
+        let xml = merge_comic_info_xml(None, &info()).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo>"));
+        assert!(xml.contains("<Title>Vol 2</Title><Series>Berserk</Series>"));
+        assert!(xml.contains("<Page Image=\"0\" Type=\"FrontCover\"/>"));
+    }
+
+    #[test]
+    fn keeps_unmanaged_elements() {
+        let existing = r#"<?xml version="1.0"?>
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Title>Old Title</Title>
+  <Publisher>Dark Horse</Publisher>
+  <Pages>
+    <Page Image="0" Type="Story"/>
+  </Pages>
+  <Manga/>
+</ComicInfo>"#;
+        let xml = merge_comic_info_xml(Some(existing), &info()).unwrap();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\"?>"));
+        assert!(xml.contains("xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\""));
+        assert!(xml.contains("<Publisher>Dark Horse</Publisher>"));
+        assert!(xml.contains("<Title>Vol 2</Title>"));
+        assert!(!xml.contains("Old Title"));
+        assert!(!xml.contains("Story"));
+        assert!(!xml.contains("<Manga/>"));
+        assert_eq!(xml.matches("<Pages>").count(), 1);
+        assert!(xml.trim_end().ends_with("</ComicInfo>"));
+    }
+
+    #[test]
+    fn fills_an_empty_file() {
+        let xml = merge_comic_info_xml(Some("<ComicInfo/>"), &info()).unwrap();
+        assert!(xml.starts_with("<ComicInfo><Title>Vol 2</Title>"));
+        assert!(xml.ends_with("</Pages></ComicInfo>"));
+    }
+
+    #[test]
+    fn round_trips() {
+        let existing = "<ComicInfo><Title>Old</Title><Notes>kept</Notes></ComicInfo>";
+        let xml = merge_comic_info_xml(Some(existing), &info()).unwrap();
+        let read = ComicInfo::from_xml(&xml).unwrap();
+
+        assert_eq!(read.title.as_deref(), Some("Vol 2"));
+        assert_eq!(read.series.as_deref(), Some("Berserk"));
+        assert_eq!(
+            read.pages.unwrap().page[0].page_type.as_deref(),
+            Some("FrontCover")
+        );
+        assert!(xml.contains("<Notes>kept</Notes>"));
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashSet;
+use image::{codecs::jpeg::JpegEncoder, DynamicImage};
+use tauri::{command, AppHandle, Manager};
+
+use crate::{
+    database::{
+        data::v2::{CoverVariants, ThumbnailSettings},
+        rebuild_cover_refs, CoverVariant, ThumbnailFormat, ThumbnailSize,
+    },
+    error::{DatabaseError, MangaImageError},
+};
+
+pub fn covers_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("covers")
+}
+
+/// covers are keyed by their source path, content and thumbnail settings,
+/// so folders with the same names never overwrite each other's covers
+pub fn cover_hash(source_path: &str, bytes: &[u8], settings: &ThumbnailSettings) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(source_path.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(bytes);
+    hasher.update(format!("{:?}{:?}", settings.format, settings.quality).as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// `app_data/covers/ab/abcdef....grid.webp`, split by the first byte of the hash
+/// so a single dir doesn't end up with thousands of files
+pub fn format_cover_img_path(
+    app_data_dir: &Path,
+    hash: &str,
+    size: ThumbnailSize,
+    format: ThumbnailFormat,
+) -> PathBuf {
+    covers_dir(app_data_dir).join(&hash[..2]).join(format!(
+        "{hash}.{}.{}",
+        size.name(),
+        format.extension()
+    ))
+}
+
+/// only compressed covers are tracked, folders can also point straight at a panel
+pub fn is_cached_cover(app_data_dir: &Path, path: &str) -> bool {
+    Path::new(path).starts_with(covers_dir(app_data_dir))
+}
+
+/// a resized cover that still has to be encoded and written to disk
+pub struct PendingThumbnail {
+    img: DynamicImage,
+    output: PathBuf,
+    format: ThumbnailFormat,
+    quality: u8,
+}
+
+impl PendingThumbnail {
+    pub fn write(self) -> Result<(), MangaImageError> {
+        if let Some(parent) = self.output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut buf = BufWriter::new(File::create(&self.output)?);
+
+        match self.format {
+            ThumbnailFormat::Jpeg => {
+                let encoder = JpegEncoder::new_with_quality(buf, self.quality);
+                self.img.write_with_encoder(encoder)?;
+            }
+            ThumbnailFormat::Webp => {
+                let rgb = self.img.to_rgb8();
+                let webp = webp::Encoder::from_rgb(&rgb, rgb.width(), rgb.height())
+                    .encode(self.quality as f32);
+                buf.write_all(&webp)?;
+                buf.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// a cover and its thumbnails. `variants` is `None` if they were already on disk.
+pub struct CompressedCover {
+    pub path: String,
+    pub variants: Option<CoverVariants>,
+    pub pending: Vec<PendingThumbnail>,
+}
+
+/// resizes the cover into every size in the settings, largest first,
+/// so each thumbnail is made from the one before it instead of the original
+pub fn make_thumbnails(
+    img: DynamicImage,
+    hash: &str,
+    app_data_dir: &Path,
+    settings: &ThumbnailSettings,
+) -> CompressedCover {
+    let mut sizes: Vec<ThumbnailSize> = settings.sizes.clone();
+    sizes.push(ThumbnailSize::Full);
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    let quality = settings.quality.quality(settings.format);
+    let path = format_cover_img_path(app_data_dir, hash, ThumbnailSize::Full, settings.format)
+        .to_string_lossy()
+        .to_string();
+
+    let mut variants = Vec::with_capacity(sizes.len());
+    let mut pending = Vec::with_capacity(sizes.len());
+    let mut source = img;
+    for size in sizes.into_iter().rev() {
+        let resized = source.thumbnail(size.max_size(), size.max_size());
+        let output = format_cover_img_path(app_data_dir, hash, size, settings.format);
+
+        variants.push(CoverVariant {
+            size,
+            path: output.to_string_lossy().to_string(),
+            width: resized.width(),
+            height: resized.height(),
+        });
+        pending.push(PendingThumbnail {
+            img: resized.clone(),
+            output,
+            format: settings.format,
+            quality,
+        });
+        source = resized;
+    }
+    variants.reverse();
+
+    CompressedCover {
+        variants: Some(CoverVariants {
+            cover_path: path.clone(),
+            variants,
+        }),
+        path,
+        pending,
+    }
+}
+
+/// checks if every thumbnail of the cover is already on disk
+pub fn thumbnails_exist(hash: &str, app_data_dir: &Path, settings: &ThumbnailSettings) -> bool {
+    settings
+        .sizes
+        .iter()
+        .chain([&ThumbnailSize::Full])
+        .all(|size| format_cover_img_path(app_data_dir, hash, *size, settings.format).exists())
+}
+
+/// thumbnails share the hash in front of their name with the cover they belong to:
+/// `covers/ab/abcdef.grid.webp` -> `covers/ab/abcdef`
+fn cover_key(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let hash = name.split('.').next().unwrap_or_default();
+    path.with_file_name(hash)
+}
+
+/// removes every file in `dir` that doesn't belong to a referenced cover, and any dirs left empty.
+/// returns the amount of removed files.
+fn remove_unreferenced(dir: &Path, referenced: &HashSet<PathBuf>) -> io::Result<usize> {
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            removed += remove_unreferenced(&path, referenced)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        } else if !referenced.contains(&cover_key(&path)) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// recounts which covers are still used by an `OsFolder`
+/// and deletes every other file in the covers dir, including covers from older versions.
+/// returns the amount of removed files.
+#[command]
+pub fn collect_cover_garbage(handle: AppHandle) -> Result<usize, DatabaseError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    let covers_dir = covers_dir(&app_data_dir);
+    if !covers_dir.exists() {
+        return Ok(0);
+    }
+
+    let referenced: HashSet<PathBuf> = rebuild_cover_refs(&handle)?
+        .into_iter()
+        .map(|cover| cover_key(Path::new(&cover)))
+        .collect();
+
+    Ok(remove_unreferenced(&covers_dir, &referenced)?)
+}
@@ -0,0 +1,307 @@
+use image::{DynamicImage, GenericImageView, GrayImage};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use tauri::{command, AppHandle};
+
+use crate::{
+    database::{
+        data::v2::{ChapterCrop, FolderCrop, MangaPanel},
+        get_chapter_crop, get_folder_crop, get_user_by_id, hash_panels, save_chapter_crop,
+        save_folder_crop, Margins,
+    },
+    error::DatabaseError,
+    fs::{decode_panel_image, read_panel_bytes},
+    layout::SPREAD_ASPECT_RATIO,
+    misc::now_timestamp,
+    split::reader_panels,
+};
+
+/// margins are detected on a downscaled page, they're stored as fractions anyway
+const DETECT_SIZE: u32 = 512;
+
+/// pixels that can stand out in a row of margin, for specks of dust and scan noise
+const NOISE_RATIO: f32 = 0.005;
+
+/// a side is never cropped past this, so a mostly empty page keeps its content
+const MAX_MARGIN: f32 = 0.25;
+
+/// the crop tolerance users start with
+pub const DEFAULT_CROP_TOLERANCE: u8 = 24;
+
+impl Margins {
+    pub fn is_empty(&self) -> bool {
+        [self.top, self.right, self.bottom, self.left]
+            .iter()
+            .all(|m| *m <= 0.0)
+    }
+
+    /// the smaller margin on every side, so a crop never cuts into either page
+    fn min(self, other: Margins) -> Margins {
+        Margins {
+            top: self.top.min(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+            left: self.left.min(other.left),
+        }
+    }
+
+    /// the crop for `?crop=` in `shelf://` urls: `top,right,bottom,left`
+    pub fn from_query(query: &str) -> Option<Margins> {
+        let mut sides = query.split(',').map(|s| s.trim().parse::<f32>().ok());
+        let margins = Margins {
+            top: sides.next()??,
+            right: sides.next()??,
+            bottom: sides.next()??,
+            left: sides.next()??,
+        };
+        let valid = [margins.top, margins.right, margins.bottom, margins.left]
+            .iter()
+            .all(|m| (0.0..=MAX_MARGIN).contains(m));
+
+        valid.then_some(margins)
+    }
+
+    pub fn crop(&self, img: &DynamicImage) -> DynamicImage {
+        let (width, height) = img.dimensions();
+        let left = (self.left * width as f32) as u32;
+        let top = (self.top * height as f32) as u32;
+        let right = (self.right * width as f32) as u32;
+        let bottom = (self.bottom * height as f32) as u32;
+
+        img.crop_imm(
+            left,
+            top,
+            width.saturating_sub(left + right).max(1),
+            height.saturating_sub(top + bottom).max(1),
+        )
+    }
+}
+
+/// `tolerance` away from the margin's color still counts as margin
+fn is_margin(
+    luma: &GrayImage,
+    pixels: impl Iterator<Item = (u32, u32)>,
+    background: u8,
+    tolerance: u8,
+) -> bool {
+    let mut total = 0;
+    let mut outliers = 0;
+    for (x, y) in pixels {
+        total += 1;
+        if luma.get_pixel(x, y).0[0].abs_diff(background) > tolerance {
+            outliers += 1;
+        }
+    }
+    outliers as f32 <= total as f32 * NOISE_RATIO
+}
+
+/// finds the uniform border around a page.
+/// returns `None` for blank pages, since they have nothing to line the crop up with.
+pub fn detect_margins(img: &DynamicImage, tolerance: u8) -> Option<Margins> {
+    let luma = img.thumbnail(DETECT_SIZE, DETECT_SIZE).to_luma8();
+    let (width, height) = luma.dimensions();
+    if width < 2 || height < 2 {
+        return None;
+    }
+
+    // the margin is whatever color most corners agree on, usually white or black
+    let mut corners = [
+        luma.get_pixel(0, 0).0[0],
+        luma.get_pixel(width - 1, 0).0[0],
+        luma.get_pixel(0, height - 1).0[0],
+        luma.get_pixel(width - 1, height - 1).0[0],
+    ];
+    corners.sort_unstable();
+    let background = corners[1];
+
+    let row = |y: u32| {
+        is_margin(
+            &luma,
+            (0..width).map(move |x| (x, y)),
+            background,
+            tolerance,
+        )
+    };
+    let top = (0..height).find(|y| !row(*y))?;
+    let bottom = (0..height).rev().find(|y| !row(*y))?;
+
+    let column = |x: u32| {
+        is_margin(
+            &luma,
+            (top..=bottom).map(move |y| (x, y)),
+            background,
+            tolerance,
+        )
+    };
+    let left = (0..width).find(|x| !column(*x))?;
+    let right = (0..width).rev().find(|x| !column(*x))?;
+
+    Some(Margins {
+        top: (top as f32 / height as f32).min(MAX_MARGIN),
+        right: ((width - 1 - right) as f32 / width as f32).min(MAX_MARGIN),
+        bottom: ((height - 1 - bottom) as f32 / height as f32).min(MAX_MARGIN),
+        left: (left as f32 / width as f32).min(MAX_MARGIN),
+    })
+}
+
+/// the margins every panel of the chapter shares, so the pages don't jump in size.
+/// spreads and blank pages are left out, their borders don't match the other pages.
+pub fn chapter_margins(panels: &[MangaPanel], tolerance: u8) -> Margins {
+    panels
+        .par_iter()
+        .filter_map(|panel| {
+            let img = decode_panel_image(read_panel_bytes(&panel.path).ok()?).ok()?;
+            if img.width() as f32 / img.height().max(1) as f32 > SPREAD_ASPECT_RATIO {
+                return None;
+            }
+            detect_margins(&img, tolerance)
+        })
+        .reduce_with(Margins::min)
+        .unwrap_or_default()
+}
+
+fn chapter_fingerprint(panels: &[MangaPanel], tolerance: u8) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[tolerance]);
+    hash_panels(&mut hasher, panels);
+    hasher.finalize().to_hex().to_string()
+}
+
+/// the margins to crop from the folder's panels, or `None` if it doesn't crop them.
+/// the folder's own setting wins over the user's default.
+/// margins are detected the first time and again once the folder's panels change.
+#[command]
+pub async fn get_auto_crop(
+    handle: AppHandle,
+    folder_path: String,
+    user_id: String,
+) -> Result<Option<Margins>, DatabaseError> {
+    let settings = get_user_by_id(handle.clone(), user_id)?.settings;
+    let is_enabled =
+        get_folder_crop(&handle, &folder_path)?.map_or(settings.auto_crop, |f| f.is_enabled);
+    if !is_enabled {
+        return Ok(None);
+    }
+
+    let panels = {
+        let (handle, folder_path) = (handle.clone(), folder_path.clone());
+        tauri::async_runtime::spawn_blocking(move || reader_panels(&handle, &folder_path)).await??
+    };
+    let fingerprint = chapter_fingerprint(&panels, settings.crop_tolerance);
+    if let Some(crop) = get_chapter_crop(&handle, &folder_path)? {
+        if crop.fingerprint == fingerprint {
+            return Ok(Some(crop.margins).filter(|m| !m.is_empty()));
+        }
+    }
+
+    let margins = tauri::async_runtime::spawn_blocking(move || {
+        chapter_margins(&panels, settings.crop_tolerance)
+    })
+    .await?;
+
+    save_chapter_crop(
+        &handle,
+        ChapterCrop {
+            folder_path,
+            fingerprint,
+            margins,
+        },
+    )?;
+
+    Ok(Some(margins).filter(|m| !m.is_empty()))
+}
+
+/// overrides the user's default for the folder, `None` goes back to following it
+#[command]
+pub fn set_folder_auto_crop(
+    handle: AppHandle,
+    folder_path: String,
+    user_id: String,
+    is_enabled: Option<bool>,
+) -> Result<(), DatabaseError> {
+    let folder_crop = is_enabled.map(|is_enabled| FolderCrop {
+        folder_path: folder_path.clone(),
+        user_id,
+        is_enabled,
+        updated_at: now_timestamp(),
+    });
+
+    save_folder_crop(&handle, &folder_path, folder_crop)
+}
+
+#[command]
+pub fn get_folder_auto_crop(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<Option<FolderCrop>, DatabaseError> {
+    get_folder_crop(&handle, &folder_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Luma;
+
+    use super::*;
+
+    /// a `width`x`height` page of `background` with a block of ink covering `x`, `y`
+    fn page(
+        width: u32,
+        height: u32,
+        background: u8,
+        x: std::ops::Range<u32>,
+        y: std::ops::Range<u32>,
+    ) -> DynamicImage {
+        let img = GrayImage::from_fn(width, height, |px, py| {
+            if x.contains(&px) && y.contains(&py) {
+                Luma([255 - background])
+            } else {
+                Luma([background])
+            }
+        });
+        DynamicImage::ImageLuma8(img)
+    }
+
+    fn assert_margins(margins: Margins, [top, right, bottom, left]: [f32; 4]) {
+        let found = [margins.top, margins.right, margins.bottom, margins.left];
+        for (found, expected) in found.into_iter().zip([top, right, bottom, left]) {
+            assert!(
+                (found - expected).abs() < 0.01,
+                "expected {expected}, found {found} in {margins:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn detects_white_margins() {
+        let img = page(1024, 1024, 255, 100..924, 200..824);
+        let margins = detect_margins(&img, DEFAULT_CROP_TOLERANCE).unwrap();
+        assert_margins(margins, [0.195, 0.098, 0.195, 0.098]);
+    }
+
+    #[test]
+    fn detects_black_margins() {
+        let img = page(800, 1200, 0, 40..720, 0..1200);
+        let margins = detect_margins(&img, DEFAULT_CROP_TOLERANCE).unwrap();
+        assert_margins(margins, [0.0, 0.1, 0.0, 0.05]);
+    }
+
+    #[test]
+    fn ignores_specks_in_the_margin() {
+        let mut img = page(1024, 1024, 255, 100..924, 200..824).to_luma8();
+        img.put_pixel(512, 50, Luma([0]));
+        let margins = detect_margins(&DynamicImage::ImageLuma8(img), DEFAULT_CROP_TOLERANCE);
+        assert_margins(margins.unwrap(), [0.195, 0.098, 0.195, 0.098]);
+    }
+
+    #[test]
+    fn caps_the_margins() {
+        let img = page(1000, 1000, 255, 450..550, 450..550);
+        let margins = detect_margins(&img, DEFAULT_CROP_TOLERANCE).unwrap();
+        assert_margins(margins, [MAX_MARGIN; 4]);
+    }
+
+    #[test]
+    fn skips_blank_pages() {
+        let img = page(800, 1200, 255, 0..0, 0..0);
+        assert_eq!(detect_margins(&img, DEFAULT_CROP_TOLERANCE), None);
+    }
+}
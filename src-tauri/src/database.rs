@@ -1,23 +1,36 @@
 use std::{
-    fs::{self, read_dir},
-    io,
+    fs, io,
     path::{Path, PathBuf},
     str::FromStr,
     sync::LazyLock,
     time::{Duration, SystemTime},
 };
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-use data::v1::{MangaPanel, MangaPanelKey, OsFolder, OsFolderKey, User};
+use data::v2::{
+    ChapterCrop, CoverRef, CoverVariants, EpubSpine, FolderCrop, FolderReadingProfile, LibraryRoot,
+    LibraryRootKey, MangaPanel, MangaPanelKey, OsFolder, OsFolderKey, PanelDimensions,
+    PanelDimensionsKey, SeriesMetadata, SeriesMetadataKey, SplitPanel, SplitPanelKey, SplitSpreads,
+    ThumbnailSettings, User, WebtoonStrip,
+};
+use hashbrown::{HashMap, HashSet};
+use native_db::transaction::{RTransaction, RwTransaction};
 use native_db::*;
 use rayon::slice::ParallelSliceMut;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tauri::{command, AppHandle, Manager};
 
+#[cfg(feature = "pdf")]
+use crate::archive::ArchiveKind;
 use crate::{
-    error::{DatabaseError, ReadDirError, SortTypeError},
-    misc::get_date_time,
+    archive::split_archive_path,
+    backup::apply_pending_restore,
+    covers::is_cached_cover,
+    error::{DatabaseError, InitError, ReadDirError, SortTypeError},
+    misc::now_timestamp,
+    repository::{Repository, RepositoryExt},
+    sort::{chapter_cmp, ChapterSortKey},
+    watcher::{unwatch_library_root, watch_library_root},
 };
 
 pub static EPISODE_TITLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -39,14 +52,191 @@ pub struct FileMetadata {
 pub struct FolderMetadata {
     contains: FolderContains,
     pub size: Option<u64>,
+    /// only set for pdfs. exports from before version 3 don't have it.
+    #[serde(default)]
+    pub page_count: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
 pub struct FolderContains {
+    /// every panel in the folder
     files: usize,
     folders: usize,
 }
 
+/// the `Manga` field of a ComicInfo.xml
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MangaReadingFlag {
+    #[default]
+    Unknown,
+    No,
+    Yes,
+    YesAndRightToLeft,
+}
+
+/// a single `<Page>` of a ComicInfo.xml
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ComicPageInfo {
+    pub image: u32,
+    pub page_type: Option<String>,
+    pub is_double_page: bool,
+}
+
+/// the thumbnails that can be generated for a cover, smallest first
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum ThumbnailSize {
+    Grid,
+    Detail,
+    Full,
+}
+
+impl ThumbnailSize {
+    /// the longest side of the thumbnail in pixels
+    pub fn max_size(self) -> u32 {
+        match self {
+            ThumbnailSize::Grid => 320,
+            ThumbnailSize::Detail => 720,
+            ThumbnailSize::Full => 1200,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThumbnailSize::Grid => "grid",
+            ThumbnailSize::Detail => "detail",
+            ThumbnailSize::Full => "full",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ThumbnailFormat {
+    #[default]
+    Jpeg,
+    Webp,
+}
+
+impl ThumbnailFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Webp => "webp",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ThumbnailQuality {
+    Low,
+    Balanced,
+    #[default]
+    High,
+}
+
+impl ThumbnailQuality {
+    /// webp holds up better at lower qualities, so it gets its own scale
+    pub fn quality(self, format: ThumbnailFormat) -> u8 {
+        match (format, self) {
+            (ThumbnailFormat::Jpeg, ThumbnailQuality::Low) => 70,
+            (ThumbnailFormat::Jpeg, ThumbnailQuality::Balanced) => 85,
+            (ThumbnailFormat::Jpeg, ThumbnailQuality::High) => 95,
+            (ThumbnailFormat::Webp, ThumbnailQuality::Low) => 60,
+            (ThumbnailFormat::Webp, ThumbnailQuality::Balanced) => 75,
+            (ThumbnailFormat::Webp, ThumbnailQuality::High) => 90,
+        }
+    }
+}
+
+/// how times are shown, timestamps are always stored in UTC
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ClockFormat {
+    /// whatever the system locale uses
+    #[default]
+    Locale,
+    TwelveHour,
+    TwentyFourHour,
+}
+
+/// a single generated thumbnail of a cover
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CoverVariant {
+    pub size: ThumbnailSize,
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// the uniform border around a page, as fractions of its width and height
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+/// the order pages are read in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ReadingDirection {
+    #[default]
+    RightToLeft,
+    LeftToRight,
+    /// one long strip of pages, for webtoons
+    Vertical,
+}
+
+/// how a page is sized to the reader
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum FitMode {
+    /// the whole page is visible
+    #[default]
+    Screen,
+    Width,
+    Height,
+    Original,
+}
+
+/// how a folder is read, once everything it inherits is filled in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ReadingProfile {
+    pub direction: ReadingDirection,
+    pub fit_mode: FitMode,
+    /// the space between pages in pixels
+    pub page_gap: u32,
+    /// shows the first page on its own in double panel mode
+    pub offset_cover: bool,
+}
+
+/// the parts of a reading profile a folder sets itself, `None` is inherited
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ReadingProfileOverrides {
+    pub direction: Option<ReadingDirection>,
+    pub fit_mode: Option<FitMode>,
+    pub page_gap: Option<u32>,
+    pub offset_cover: Option<bool>,
+}
+
+/// the rows of a panel that end up in a webtoon tile, in the panel's own pixels
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TileSource {
+    pub path: String,
+    pub y: u32,
+    pub height: u32,
+    /// where the rows start in the tile, in the tile's pixels
+    pub offset: u32,
+}
+
+/// a viewport sized piece of a webtoon, cut from tall panels or joined from short ones
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct WebtoonTile {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// the panel that takes up most of the tile, read progress is saved to it
+    pub panel_path: String,
+    pub sources: Vec<TileSource>,
+}
+
 // Serialize SystemTime as u64 (seconds since epoch)
 fn _serialize_system_time<S>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -73,7 +263,12 @@ where
 impl FileMetadata {
     // Constructor to get metadata of a file
     pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
-        let metadata = std::fs::metadata(path).ok();
+        // panels inside of an archive share the archive's metadata,
+        // so rewriting the archive marks all of its panels as stale
+        let metadata = match split_archive_path(&path) {
+            Some((archive, _)) => std::fs::metadata(archive).ok(),
+            None => std::fs::metadata(path).ok(),
+        };
 
         if let Some(metadata) = metadata {
             let modified = metadata.modified().ok();
@@ -95,7 +290,7 @@ impl FileMetadata {
 
 impl FolderMetadata {
     pub fn from_path(path: impl AsRef<Path>, img_len: usize, dir_len: usize) -> Option<Self> {
-        let metadata = std::fs::metadata(path).ok();
+        let metadata = std::fs::metadata(&path).ok();
         if let Some(metadata) = metadata {
             let size = Some(metadata.len());
             let contains = FolderContains {
@@ -103,7 +298,18 @@ impl FolderMetadata {
                 folders: dir_len,
             };
 
-            return Some(Self { size, contains });
+            // every page of a pdf is listed as one panel
+            #[cfg(feature = "pdf")]
+            let page_count =
+                (ArchiveKind::from_path(&path) == Some(ArchiveKind::Pdf)).then_some(img_len);
+            #[cfg(not(feature = "pdf"))]
+            let page_count = None;
+
+            return Some(Self {
+                size,
+                contains,
+                page_count,
+            });
         }
         None
     }
@@ -115,10 +321,39 @@ pub mod data {
     use serde::{Deserialize, Serialize};
 
     pub mod v1 {
-        use crate::database::{FileMetadata, FolderMetadata};
+        use crate::database::{
+            ComicPageInfo, CoverVariant, FileMetadata, FolderContains, MangaReadingFlag, Margins,
+            WebtoonTile,
+        };
 
         use super::*;
 
+        /// `FolderMetadata` from before pdfs stored their page count
+        #[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+        pub struct FolderMetadata {
+            contains: FolderContains,
+            pub size: Option<u64>,
+        }
+
+        impl From<FolderMetadata> for crate::database::FolderMetadata {
+            fn from(metadata: FolderMetadata) -> Self {
+                crate::database::FolderMetadata {
+                    contains: metadata.contains,
+                    size: metadata.size,
+                    page_count: None,
+                }
+            }
+        }
+
+        impl From<crate::database::FolderMetadata> for FolderMetadata {
+            fn from(metadata: crate::database::FolderMetadata) -> Self {
+                FolderMetadata {
+                    contains: metadata.contains,
+                    size: metadata.size,
+                }
+            }
+        }
+
         /// mangashelf user type
         #[derive(Serialize, Deserialize, Debug)]
         #[native_model(id = 1, version = 1)]
@@ -187,182 +422,645 @@ pub mod data {
             pub update_date: String,
             pub update_time: String,
         }
-    }
-}
 
-static DBMODELS: LazyLock<Models> = LazyLock::new(|| {
-    let mut models = Models::new();
-    models.define::<data::v1::User>().unwrap();
-    models.define::<data::v1::OsFolder>().unwrap();
-    models.define::<data::v1::MangaPanel>().unwrap();
-    models
-});
+        /// series metadata read from a folder's or archive's ComicInfo.xml
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 5, version = 1)]
+        #[native_db]
+        pub struct SeriesMetadata {
+            #[primary_key]
+            pub folder_path: String,
+            #[secondary_key]
+            pub user_id: String,
+            pub title: Option<String>,
+            pub series: Option<String>,
+            pub volume: Option<i32>,
+            pub number: Option<String>,
+            pub writers: Vec<String>,
+            pub pencillers: Vec<String>,
+            pub summary: Option<String>,
+            pub genres: Vec<String>,
+            pub language: Option<String>,
+            pub manga: MangaReadingFlag,
+            pub pages: Vec<ComicPageInfo>,
+        }
 
-pub trait HasPath {
-    fn path(&self) -> &str;
-}
+        /// a compressed cover in the app data dir, and how many `OsFolder`s point to it
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 7, version = 1)]
+        #[native_db]
+        pub struct CoverRef {
+            #[primary_key]
+            pub path: String,
+            pub ref_count: u32,
+        }
 
-pub trait HasTitle {
-    fn title(&self) -> &str;
-}
+        /// every thumbnail generated for a cover, smallest first
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 9, version = 1)]
+        #[native_db]
+        pub struct CoverVariants {
+            #[primary_key]
+            pub cover_path: String,
+            pub variants: Vec<CoverVariant>,
+        }
 
-pub trait HasDatetime {
-    fn date(&self) -> &str;
-    fn time(&self) -> &str;
-    fn get_naive_datetime(&self) -> Result<NaiveDateTime, chrono::ParseError> {
-        let date = &self.date(); // "2024-11-30"
-        let mut time = self.time().to_string(); // "10:43pm"
+        /// the size of a panel, read from its image header
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        #[native_model(id = 10, version = 1)]
+        #[native_db]
+        pub struct PanelDimensions {
+            #[primary_key]
+            pub path: String,
+            #[secondary_key]
+            pub parent_path: String,
+            pub width: u32,
+            pub height: u32,
+            /// width / height, so anything above 1 is wider than it is tall
+            pub aspect_ratio: f32,
+        }
 
-        time.replace_range(time.len() - 2.., "");
+        impl PanelDimensions {
+            pub fn new(path: String, parent_path: String, width: u32, height: u32) -> Self {
+                PanelDimensions {
+                    path,
+                    parent_path,
+                    width,
+                    height,
+                    aspect_ratio: width as f32 / height.max(1) as f32,
+                }
+            }
+        }
 
-        let naive_date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
-        let naive_time = NaiveTime::parse_from_str(&time, "%H:%M")?;
+        /// the margins cropped from every panel of a folder
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 15, version = 1)]
+        #[native_db]
+        pub struct ChapterCrop {
+            #[primary_key]
+            pub folder_path: String,
+            /// changes with the folder's panels and the tolerance, so the margins are detected again
+            pub fingerprint: String,
+            pub margins: Margins,
+        }
 
-        // Return combined NaiveDateTime
-        Ok(NaiveDateTime::new(naive_date, naive_time))
-    }
-}
+        /// the panels of an epub in spine (reading) order,
+        /// so its opf is only parsed when the epub is scanned
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 19, version = 1)]
+        #[native_db]
+        pub struct EpubSpine {
+            #[primary_key]
+            pub folder_path: String,
+            /// virtual panel paths
+            pub pages: Vec<String>,
+        }
 
-// path
-impl HasPath for OsFolder {
-    fn path(&self) -> &str {
-        self.path.as_ref()
+        /// the panels of a folder cut into tiles for webtoon mode, cached in the app data dir
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 18, version = 1)]
+        #[native_db]
+        pub struct WebtoonStrip {
+            #[primary_key]
+            pub folder_path: String,
+            /// changes with the folder's panels, so the tiles are cut again
+            pub fingerprint: String,
+            /// the tiles are kept together in this dir
+            pub dir: String,
+            pub tiles: Vec<WebtoonTile>,
+        }
     }
-}
 
-impl HasPath for MangaPanel {
-    fn path(&self) -> &str {
-        self.path.as_ref()
-    }
-}
+    /// every `update_date` and `update_time` display string is an `updated_at` UTC timestamp,
+    /// in milliseconds. models without them are the same as in v1,
+    /// and models that got their timestamps before they were ever released are only defined here.
+    pub mod v2 {
+        use crate::{
+            crop::DEFAULT_CROP_TOLERANCE,
+            database::{
+                ClockFormat, FileMetadata, FolderMetadata, ReadingProfile, ReadingProfileOverrides,
+                ThumbnailFormat, ThumbnailQuality, ThumbnailSize,
+            },
+            misc::{format_legacy_date_time, parse_legacy_date_time},
+        };
 
-// title
-impl HasTitle for OsFolder {
-    fn title(&self) -> &str {
-        self.title.as_ref()
-    }
-}
+        pub use super::v1::{
+            ChapterCrop, CoverRef, CoverVariants, EpubSpine, PanelDimensions, SeriesMetadata,
+            WebtoonStrip,
+        };
+        pub(crate) use super::v1::{PanelDimensionsKey, SeriesMetadataKey};
 
-impl HasTitle for MangaPanel {
-    fn title(&self) -> &str {
-        self.title.as_ref()
-    }
-}
+        use super::*;
 
-// datetime
-impl HasDatetime for OsFolder {
-    fn date(&self) -> &str {
-        &self.update_date
-    }
-    fn time(&self) -> &str {
-        &self.update_time
-    }
-}
+        /// unreadable v1 strings become 0, so they sort as the oldest
+        fn from_legacy(date: &str, time: &str) -> i64 {
+            parse_legacy_date_time(date, time).unwrap_or_default()
+        }
 
-impl HasDatetime for MangaPanel {
-    fn date(&self) -> &str {
-        &self.update_date
-    }
-    fn time(&self) -> &str {
-        &self.update_time
-    }
-}
+        /// mangashelf user type
+        #[derive(Serialize, Deserialize, Debug)]
+        #[native_model(id = 1, version = 2, from = v1::User)]
+        #[native_db]
+        pub struct User {
+            #[primary_key]
+            pub id: String,
+            #[secondary_key(unique)]
+            pub username: String,
+            pub settings: Settings,
+            pub last_read_manga_folder: Option<OsFolder>,
+        }
 
-impl MangaPanel {
-    pub fn new(
-        user_id: String,
-        parent_path: String,
-        path: String,
-        update_date: String,
-        update_time: String,
-    ) -> Result<MangaPanel, ReadDirError> {
-        let title = Path::new(&path)
-            .file_name()
-            .ok_or_else(|| {
-                ReadDirError::IoError(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("'{path}' contained invalid characters when trying to get the OsVideo title."),
-                ))
-            })?
-            .to_string_lossy()
-            .to_string();
+        impl From<v1::User> for User {
+            fn from(user: v1::User) -> Self {
+                User {
+                    id: user.id,
+                    username: user.username,
+                    settings: user.settings.into(),
+                    last_read_manga_folder: user.last_read_manga_folder.map(Into::into),
+                }
+            }
+        }
 
-        let metadata = FileMetadata::from_path(&path);
+        impl From<User> for v1::User {
+            fn from(user: User) -> Self {
+                v1::User {
+                    id: user.id,
+                    username: user.username,
+                    settings: user.settings.into(),
+                    last_read_manga_folder: user.last_read_manga_folder.map(Into::into),
+                }
+            }
+        }
 
-        // Create MangaPanel instance
-        let vid = MangaPanel {
-            user_id,
-            parent_path,
-            path,
-            title,
-            metadata,
-            is_read: false,
-            update_date,
-            update_time,
-        };
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 2, version = 2, from = v1::Settings)]
+        #[native_db]
+        pub struct Settings {
+            #[primary_key]
+            pub user_id: String,
+            pub mpv_path: Option<String>,
+            pub plugins_path: Option<String>,
+            pub autoplay: bool,
+            /// 12 or 24 hour times, dates always follow the locale
+            pub clock: ClockFormat,
+            /// crops page margins by default, folders can override it
+            pub auto_crop: bool,
+            /// how far a pixel can be from the margin's color and still count as margin
+            pub crop_tolerance: u8,
+            /// the reading profile folders fall back to when neither they nor their parents set it
+            pub reading_profile: ReadingProfile,
+            pub updated_at: i64,
+        }
 
-        Ok(vid)
-    }
+        impl From<v1::Settings> for Settings {
+            fn from(settings: v1::Settings) -> Self {
+                Settings {
+                    updated_at: from_legacy(&settings.update_date, &settings.update_time),
+                    user_id: settings.user_id,
+                    mpv_path: settings.mpv_path,
+                    plugins_path: settings.plugins_path,
+                    autoplay: settings.autoplay,
+                    clock: ClockFormat::default(),
+                    auto_crop: false,
+                    crop_tolerance: DEFAULT_CROP_TOLERANCE,
+                    reading_profile: ReadingProfile::default(),
+                }
+            }
+        }
 
-    pub fn is_stale_metadata(&self) -> bool {
-        if let Some(ref current_metadata) = self.metadata {
-            // Fetch the current metadata of the file
-            match FileMetadata::from_path(&self.path) {
-                Some(new_metadata) => {
-                    // Compare the modified time and size
-                    //current_metadata.modified != new_metadata.modified ||
-                    current_metadata.size != new_metadata.size
+        impl From<Settings> for v1::Settings {
+            fn from(settings: Settings) -> Self {
+                let (update_date, update_time) = format_legacy_date_time(settings.updated_at);
+                v1::Settings {
+                    user_id: settings.user_id,
+                    mpv_path: settings.mpv_path,
+                    plugins_path: settings.plugins_path,
+                    autoplay: settings.autoplay,
+                    update_date,
+                    update_time,
                 }
-                None => true, // If metadata can't be fetched, assume it's stale
             }
-        } else {
-            true // If no metadata is stored, consider it stale
         }
-    }
 
-    // Update the manga panel's metadata
-    pub fn _update_metadata(&mut self) {
-        if let Some(new_metadata) = FileMetadata::from_path(&self.path) {
-            self.metadata = Some(new_metadata);
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 3, version = 2, from = v1::OsFolder)]
+        #[native_db]
+        pub struct OsFolder {
+            #[secondary_key]
+            pub user_id: String,
+            #[primary_key]
+            pub path: String,
+            pub title: String,
+            #[secondary_key]
+            pub parent_path: Option<String>,
+            pub last_read_panel: Option<MangaPanel>,
+            pub cover_img_path: Option<String>,
+            pub metadata: Option<FolderMetadata>,
+            // everything below should be put into a struct at some point to organize
+            pub is_manga_folder: bool,
+            pub is_double_panels: bool,
+            pub is_read: bool,
+            pub zoom: usize,
+            pub is_hidden: bool,
+            pub updated_at: i64,
         }
-    }
-}
 
-impl OsFolder {
-    pub fn delete_app_data_cover_folder(&self, app_data_dir: &Path) -> Result<(), io::Error> {
-        //dbg!(app_data_dir);
-        for e in read_dir(app_data_dir.join("covers"))? {
-            let e = e?;
-            let name = e.file_name();
-            let name = name.to_str();
-            if e.path().is_dir() {
-                if let Some(dirname) = name {
-                    if self.title == dirname {
-                        fs::remove_dir_all(e.path())?;
-                    }
+        impl From<v1::OsFolder> for OsFolder {
+            fn from(folder: v1::OsFolder) -> Self {
+                OsFolder {
+                    updated_at: from_legacy(&folder.update_date, &folder.update_time),
+                    user_id: folder.user_id,
+                    path: folder.path,
+                    title: folder.title,
+                    parent_path: folder.parent_path,
+                    last_read_panel: folder.last_read_panel.map(Into::into),
+                    cover_img_path: folder.cover_img_path,
+                    metadata: folder.metadata.map(Into::into),
+                    is_manga_folder: folder.is_manga_folder,
+                    is_double_panels: folder.is_double_panels,
+                    is_read: folder.is_read,
+                    zoom: folder.zoom,
+                    is_hidden: folder.is_hidden,
                 }
-            } else if e.path().is_file() {
-                if let Some(ci_path) = &self.cover_img_path {
-                    if let Some(filename) = name {
-                        if ci_path == filename {
-                            fs::remove_file(e.path())?;
-                        }
-                    }
+            }
+        }
+
+        impl From<OsFolder> for v1::OsFolder {
+            fn from(folder: OsFolder) -> Self {
+                let (update_date, update_time) = format_legacy_date_time(folder.updated_at);
+                v1::OsFolder {
+                    user_id: folder.user_id,
+                    path: folder.path,
+                    title: folder.title,
+                    parent_path: folder.parent_path,
+                    last_read_panel: folder.last_read_panel.map(Into::into),
+                    cover_img_path: folder.cover_img_path,
+                    metadata: folder.metadata.map(Into::into),
+                    is_manga_folder: folder.is_manga_folder,
+                    is_double_panels: folder.is_double_panels,
+                    is_read: folder.is_read,
+                    zoom: folder.zoom,
+                    is_hidden: folder.is_hidden,
+                    update_date,
+                    update_time,
                 }
             }
         }
-        Ok(())
-    }
-}
 
-pub fn init_database(app_data_dir: &Path, handle: &AppHandle) -> Result<(), db_type::Error> {
-    if !app_data_dir.exists() {
-        std::fs::create_dir_all(app_data_dir.join("covers"))?;
+        #[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+        #[native_model(id = 4, version = 2, from = v1::MangaPanel)]
+        #[native_db]
+        pub struct MangaPanel {
+            #[secondary_key]
+            pub user_id: String,
+            #[primary_key]
+            pub path: String,
+            pub title: String,
+            #[secondary_key]
+            pub parent_path: String,
+            pub metadata: Option<FileMetadata>,
+            pub is_read: bool,
+            pub updated_at: i64,
+        }
+
+        impl From<v1::MangaPanel> for MangaPanel {
+            fn from(panel: v1::MangaPanel) -> Self {
+                MangaPanel {
+                    updated_at: from_legacy(&panel.update_date, &panel.update_time),
+                    user_id: panel.user_id,
+                    path: panel.path,
+                    title: panel.title,
+                    parent_path: panel.parent_path,
+                    metadata: panel.metadata,
+                    is_read: panel.is_read,
+                }
+            }
+        }
+
+        impl From<MangaPanel> for v1::MangaPanel {
+            fn from(panel: MangaPanel) -> Self {
+                let (update_date, update_time) = format_legacy_date_time(panel.updated_at);
+                v1::MangaPanel {
+                    user_id: panel.user_id,
+                    path: panel.path,
+                    title: panel.title,
+                    parent_path: panel.parent_path,
+                    metadata: panel.metadata,
+                    is_read: panel.is_read,
+                    update_date,
+                    update_time,
+                }
+            }
+        }
+
+        /// a folder that was added to the library.
+        /// roots on a drive that isn't plugged in are flagged offline instead of being deleted.
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 6, version = 1)]
+        #[native_db]
+        pub struct LibraryRoot {
+            #[primary_key]
+            pub path: String,
+            #[secondary_key]
+            pub user_id: String,
+            pub is_offline: bool,
+            pub updated_at: i64,
+        }
+
+        /// how covers get compressed for a user
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 8, version = 1)]
+        #[native_db]
+        pub struct ThumbnailSettings {
+            #[primary_key]
+            pub user_id: String,
+            pub format: ThumbnailFormat,
+            pub quality: ThumbnailQuality,
+            /// the full size is always generated, since it's the folder's `cover_img_path`
+            pub sizes: Vec<ThumbnailSize>,
+            pub updated_at: i64,
+        }
+
+        /// folders that have their double page scans split into two panels
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 11, version = 1)]
+        #[native_db]
+        pub struct SplitSpreads {
+            #[primary_key]
+            pub folder_path: String,
+            #[secondary_key]
+            pub user_id: String,
+            pub is_enabled: bool,
+            /// the right half is shown first, like a printed manga
+            pub right_to_left: bool,
+            pub updated_at: i64,
+        }
+
+        /// the halves of a wide panel, cached in the app data dir
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 12, version = 1)]
+        #[native_db]
+        pub struct SplitPanel {
+            #[primary_key]
+            pub source_path: String,
+            #[secondary_key]
+            pub parent_path: String,
+            /// the panel as it was when it got split,
+            /// it gets split again once `is_stale_metadata` says the file changed
+            pub source: MangaPanel,
+            pub left: String,
+            pub right: String,
+        }
+
+        /// a folder that crops its margins differently from the user's default
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 14, version = 1)]
+        #[native_db]
+        pub struct FolderCrop {
+            #[primary_key]
+            pub folder_path: String,
+            #[secondary_key]
+            pub user_id: String,
+            pub is_enabled: bool,
+            pub updated_at: i64,
+        }
+
+        /// the parts of the reading profile a folder sets for itself and its child folders
+        #[derive(Serialize, Deserialize, Clone, Debug)]
+        #[native_model(id = 17, version = 1)]
+        #[native_db]
+        pub struct FolderReadingProfile {
+            #[primary_key]
+            pub folder_path: String,
+            #[secondary_key]
+            pub user_id: String,
+            pub profile: ReadingProfileOverrides,
+            pub updated_at: i64,
+        }
+    }
+}
+
+static DBMODELS: LazyLock<Models> = LazyLock::new(|| {
+    let mut models = Models::new();
+    // the versions that get migrated, the old ones first
+    models.define::<data::v1::User>().unwrap();
+    models.define::<data::v1::OsFolder>().unwrap();
+    models.define::<data::v1::MangaPanel>().unwrap();
+    models.define::<data::v2::User>().unwrap();
+    models.define::<data::v2::OsFolder>().unwrap();
+    models.define::<data::v2::MangaPanel>().unwrap();
+    // native_db marks models as legacy by version alone, whatever their id,
+    // so the models that only have one version go last to leave the v2 models as the current ones
+    models.define::<data::v2::LibraryRoot>().unwrap();
+    models.define::<data::v2::ThumbnailSettings>().unwrap();
+    models.define::<data::v2::SplitSpreads>().unwrap();
+    models.define::<data::v2::SplitPanel>().unwrap();
+    models.define::<data::v2::FolderCrop>().unwrap();
+    models.define::<data::v2::FolderReadingProfile>().unwrap();
+    models.define::<data::v2::SeriesMetadata>().unwrap();
+    models.define::<data::v2::CoverRef>().unwrap();
+    models.define::<data::v2::CoverVariants>().unwrap();
+    models.define::<data::v2::PanelDimensions>().unwrap();
+    models.define::<data::v2::ChapterCrop>().unwrap();
+    models.define::<data::v2::WebtoonStrip>().unwrap();
+    models.define::<data::v2::EpubSpine>().unwrap();
+    models
+});
+
+pub trait HasPath {
+    fn path(&self) -> &str;
+}
+
+pub trait HasTitle {
+    fn title(&self) -> &str;
+}
+
+pub trait HasTimestamp {
+    /// milliseconds since the unix epoch in UTC
+    fn updated_at(&self) -> i64;
+}
+
+// path
+impl HasPath for OsFolder {
+    fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+}
+
+impl HasPath for MangaPanel {
+    fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+}
+
+// title
+impl HasTitle for OsFolder {
+    fn title(&self) -> &str {
+        self.title.as_ref()
+    }
+}
+
+impl HasTitle for MangaPanel {
+    fn title(&self) -> &str {
+        self.title.as_ref()
+    }
+}
+
+// timestamp
+impl HasTimestamp for OsFolder {
+    fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+impl HasTimestamp for MangaPanel {
+    fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+/// reading moves `last_read_manga_folder` without touching the settings, so either can be newer
+impl HasTimestamp for User {
+    fn updated_at(&self) -> i64 {
+        let last_read = self.last_read_manga_folder.as_ref();
+        self.settings
+            .updated_at
+            .max(last_read.map_or(i64::MIN, |f| f.updated_at))
+    }
+}
+
+impl MangaPanel {
+    pub fn new(
+        user_id: String,
+        parent_path: String,
+        path: String,
+        updated_at: i64,
+    ) -> Result<MangaPanel, ReadDirError> {
+        let title = Path::new(&path)
+            .file_name()
+            .ok_or_else(|| {
+                ReadDirError::IoError(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("'{path}' contained invalid characters when trying to get the OsVideo title."),
+                ))
+            })?
+            .to_string_lossy()
+            .to_string();
+
+        let metadata = FileMetadata::from_path(&path);
+
+        // Create MangaPanel instance
+        let vid = MangaPanel {
+            user_id,
+            parent_path,
+            path,
+            title,
+            metadata,
+            is_read: false,
+            updated_at,
+        };
+
+        Ok(vid)
+    }
+
+    pub fn is_stale_metadata(&self) -> bool {
+        if let Some(ref current_metadata) = self.metadata {
+            // Fetch the current metadata of the file
+            match FileMetadata::from_path(&self.path) {
+                Some(new_metadata) => {
+                    // Compare the modified time and size
+                    //current_metadata.modified != new_metadata.modified ||
+                    current_metadata.size != new_metadata.size
+                }
+                None => true, // If metadata can't be fetched, assume it's stale
+            }
+        } else {
+            true // If no metadata is stored, consider it stale
+        }
+    }
+
+    // Update the manga panel's metadata
+    pub fn _update_metadata(&mut self) {
+        if let Some(new_metadata) = FileMetadata::from_path(&self.path) {
+            self.metadata = Some(new_metadata);
+        }
+    }
+}
+
+/// hashes the panels' paths, sizes and modified times,
+/// so whatever is cached from them is made again once one of them changes
+pub fn hash_panels(hasher: &mut blake3::Hasher, panels: &[MangaPanel]) {
+    for panel in panels {
+        hasher.update(panel.path.as_bytes());
+        hasher.update(&[0]);
+        let metadata = panel.metadata.as_ref();
+        let size = metadata.and_then(|m| m.size);
+        hasher.update(&size.unwrap_or_default().to_le_bytes());
+        let modified = metadata
+            .and_then(|m| m.modified)
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok());
+        hasher.update(&modified.unwrap_or_default().as_nanos().to_le_bytes());
+    }
+}
+
+/// the newest version in `data`, older tables are migrated to it on startup
+const SCHEMA_VERSION: u32 = 2;
+
+/// whether a model still has rows in the table of an older version
+fn needs_migration(db: &Database) -> Result<bool, db_type::Error> {
+    let rtx = db.r_transaction()?;
+    let len = rtx.len();
+    let legacy_rows = [
+        len.primary::<data::v1::User>()?,
+        len.primary::<data::v1::OsFolder>()?,
+        len.primary::<data::v1::MangaPanel>()?,
+    ];
+
+    Ok(legacy_rows.into_iter().any(|rows| rows > 0))
+}
+
+/// moves every row into the table of its newest version.
+/// it's one transaction, so a migration that fails leaves the database as it was.
+fn migrate_models(db: &Database) -> Result<(), db_type::Error> {
+    let rw = db.rw_transaction()?;
+    rw.migrate::<data::v2::User>()?;
+    rw.migrate::<data::v2::OsFolder>()?;
+    rw.migrate::<data::v2::MangaPanel>()?;
+    rw.commit()
+}
+
+/// `main.rdb` -> `main.before-v2.1732968900000.rdb`, next to the database
+fn backup_before_migration(db_path: &Path) -> io::Result<PathBuf> {
+    let backup =
+        db_path.with_extension(format!("before-v{SCHEMA_VERSION}.{}.rdb", now_timestamp()));
+    fs::copy(db_path, &backup)?;
+    Ok(backup)
+}
+
+pub fn init_database(app_data_dir: &Path, handle: &AppHandle) -> Result<(), InitError> {
+    if !app_data_dir.exists() {
+        std::fs::create_dir_all(app_data_dir.join("covers"))?;
     }
     let db_path = app_data_dir.join("main").with_extension("rdb");
-    Builder::new().create(&DBMODELS, &db_path)?;
+    apply_pending_restore(app_data_dir, &db_path)?;
+    let mut db = Builder::new().create(&DBMODELS, &db_path)?;
+
+    if needs_migration(&db)? {
+        // closed while it's copied, so the backup is a whole database
+        drop(db);
+        let backup = backup_before_migration(&db_path).map_err(|e| InitError::Backup {
+            path: db_path.clone(),
+            source: e,
+        })?;
+        db = Builder::new().open(&DBMODELS, &db_path)?;
+        migrate_models(&db).map_err(|e| InitError::Migration {
+            version: SCHEMA_VERSION,
+            backup,
+            source: e,
+        })?;
+    }
 
-    handle.manage(db_path);
+    handle.manage(Repository::new(db, &DBMODELS));
     Ok(())
 }
 
@@ -374,20 +1072,16 @@ pub enum SortType {
     None,
     EpisodeTitleRegex,
     Updated,
+    Natural,
 }
 
 impl SortType {
     pub fn sort<T>(&self) -> impl Fn(&T, &T) -> std::cmp::Ordering
     where
-        T: HasDatetime + HasTitle,
+        T: HasTimestamp + HasTitle,
     {
         match self {
-            SortType::Updated => |a: &T, b: &T| {
-                let a_dt = a.get_naive_datetime().unwrap_or_default();
-                let b_dt = b.get_naive_datetime().unwrap_or_default();
-                //println!("a_dt: {:?}, b_dt: {:?}", a_dt, b_dt);
-                b_dt.cmp(&a_dt)
-            },
+            SortType::Updated => |a: &T, b: &T| b.updated_at().cmp(&a.updated_at()),
             SortType::EpisodeTitleRegex => |a: &T, b: &T| {
                 let num_a = EPISODE_TITLE_REGEX
                     .captures(a.title())
@@ -403,9 +1097,38 @@ impl SortType {
 
                 num_a.cmp(&num_b)
             },
+            SortType::Natural => |a: &T, b: &T| chapter_cmp(a.title(), b.title()),
             _ => |_: &T, _: &T| std::cmp::Ordering::Equal,
         }
     }
+
+    /// natural sorting parses every title once up front instead of on every compare
+    pub fn par_sort<T>(&self, items: &mut [T])
+    where
+        T: HasTimestamp + HasTitle + Send,
+    {
+        match self {
+            SortType::Natural => items.par_sort_by_cached_key(|i| ChapterSortKey::new(i.title())),
+            _ => items.par_sort_by(self.sort()),
+        }
+    }
+}
+
+/// sorts panels into reading order.
+/// epubs follow their spine, everything else is sorted by the volume and chapter in the title.
+pub fn sort_panels(spine: Option<&EpubSpine>, panels: &mut [MangaPanel]) {
+    match spine {
+        Some(spine) => {
+            let order: HashMap<&str, usize> = spine
+                .pages
+                .iter()
+                .enumerate()
+                .map(|(i, page)| (page.as_str(), i))
+                .collect();
+            panels.par_sort_by_key(|p| order.get(p.path.as_str()).copied().unwrap_or(usize::MAX));
+        }
+        None => SortType::Natural.par_sort(panels),
+    }
 }
 
 impl FromStr for SortType {
@@ -415,11 +1138,22 @@ impl FromStr for SortType {
             "none" => Ok(Self::None),
             "episode_title_regex" => Ok(Self::EpisodeTitleRegex),
             "updated" => Ok(Self::Updated),
+            "natural" => Ok(Self::Natural),
             _ => Err(SortTypeError::FromStr(s.to_string())),
         }
     }
 }
 
+/// a folder as the library pages list it.
+/// `is_offline` is resolved from `LibraryRoot` on every read, so it's never stored with the folder.
+#[derive(Serialize, Clone, Debug)]
+pub struct OsFolderEntry {
+    #[serde(flatten)]
+    pub folder: OsFolder,
+    /// the folder's library root is on a drive that isn't plugged in
+    pub is_offline: bool,
+}
+
 // tauri cmds
 
 #[command]
@@ -427,11 +1161,10 @@ pub fn get_os_folders(
     handle: AppHandle,
     user_id: String,
     sort_type: String,
-) -> Result<Vec<OsFolder>, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
+) -> Result<Vec<OsFolderEntry>, DatabaseError> {
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let mut folders: Vec<OsFolder> = rtx
         .scan()
         .secondary(OsFolderKey::user_id)?
@@ -440,7 +1173,7 @@ pub fn get_os_folders(
 
     folders.retain(|folder| folder.parent_path.is_none());
     let sort_type = SortType::from_str(&sort_type)?;
-    folders.par_sort_by(sort_type.sort());
+    sort_type.par_sort(&mut folders);
 
     if folders.is_empty() {
         return Err(DatabaseError::OsFoldersNotFound(format!(
@@ -448,22 +1181,21 @@ pub fn get_os_folders(
         )));
     }
 
-    Ok(folders)
+    folder_entries(&rtx, folders)
 }
 
 #[command]
 pub fn get_os_folder_by_path(
     handle: AppHandle,
     folder_path: String,
-) -> Result<OsFolder, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
+) -> Result<OsFolderEntry, DatabaseError> {
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let folder: Option<OsFolder> = rtx.get().primary(folder_path.as_str())?;
 
     if let Some(folder) = folder {
-        return Ok(folder);
+        return Ok(folder_entries(&rtx, vec![folder])?.remove(0));
     }
 
     Err(DatabaseError::OsFoldersNotFound(format!(
@@ -476,11 +1208,10 @@ pub fn get_os_folders_by_path(
     handle: AppHandle,
     parent_path: String,
     sort_type: String,
-) -> Result<Vec<OsFolder>, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
+) -> Result<Vec<OsFolderEntry>, DatabaseError> {
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let mut folders: Vec<OsFolder> = rtx
         .scan()
         .secondary(OsFolderKey::parent_path)? // Specify the index for filtering
@@ -496,9 +1227,36 @@ pub fn get_os_folders_by_path(
     }
 
     let sort_type = SortType::from_str(&sort_type)?;
-    folders.par_sort_by(sort_type.sort());
+    sort_type.par_sort(&mut folders);
 
-    Ok(folders)
+    folder_entries(&rtx, folders)
+}
+
+/// flags the folders that are under an offline library root
+fn folder_entries(
+    rtx: &RTransaction,
+    folders: Vec<OsFolder>,
+) -> Result<Vec<OsFolderEntry>, DatabaseError> {
+    let offline_roots: Vec<PathBuf> = rtx
+        .scan()
+        .primary::<LibraryRoot>()?
+        .all()?
+        .filter_map(|result| result.ok())
+        .filter(|root| root.is_offline)
+        .map(|root| PathBuf::from(root.path))
+        .collect();
+
+    let entries = folders
+        .into_iter()
+        .map(|folder| OsFolderEntry {
+            is_offline: offline_roots
+                .iter()
+                .any(|root| Path::new(&folder.path).starts_with(root)),
+            folder,
+        })
+        .collect();
+
+    Ok(entries)
 }
 
 #[command]
@@ -507,10 +1265,9 @@ pub fn update_os_folders(
     os_folders: Vec<OsFolder>,
     user: Option<User>,
 ) -> Result<(), DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
-    let rwtx = db.rw_transaction()?;
-    let (date, time) = get_date_time();
+    let db = handle.repo();
+    let rwtx = db.write()?;
+    let updated_at = now_timestamp();
 
     // the user only gets passed from the reader
     // meaning that we should update the last nested folder read
@@ -525,8 +1282,7 @@ pub fn update_os_folders(
     }
 
     for mut folder in os_folders {
-        folder.update_date = date.clone();
-        folder.update_time = time.clone();
+        folder.updated_at = updated_at;
 
         rwtx.upsert(folder)?;
     }
@@ -536,42 +1292,14 @@ pub fn update_os_folders(
     Ok(())
 }
 
-#[command]
-pub fn update_panels(
-    handle: &AppHandle,
-    os_videos: Vec<MangaPanel>,
-    watched: Option<bool>,
-) -> Result<(), DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
-    let rtx = db.rw_transaction()?;
-    let (date, time) = get_date_time();
-
-    for mut vid in os_videos {
-        vid.update_date = date.clone();
-        vid.update_time = time.clone();
-
-        if let Some(watched) = watched {
-            vid.is_read = watched;
-        }
-
-        rtx.upsert(vid)?;
-    }
-
-    rtx.commit()?;
-
-    Ok(())
-}
-
 #[command]
 pub fn get_panels(
     handle: AppHandle,
     parent_path: String,
 ) -> Result<Vec<MangaPanel>, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let mut panels: Vec<MangaPanel> = rtx
         .scan()
         .secondary(MangaPanelKey::parent_path)?
@@ -588,49 +1316,749 @@ pub fn get_panels(
         )));
     }
 
-    panels.par_sort_by(SortType::sort(&SortType::EpisodeTitleRegex));
+    let spine: Option<EpubSpine> = rtx.get().primary(parent_path.as_str())?;
+    sort_panels(spine.as_ref(), &mut panels);
 
     //println!("{:#?}", folders);
 
     Ok(panels)
 }
 
-pub fn delete_panels(handle: &AppHandle, panels: Vec<MangaPanel>) -> Result<(), DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
-    let rwtx = db.rw_transaction()?;
-
-    for p in panels {
-        rwtx.remove(p)?;
-    }
-
-    rwtx.commit()?;
-
-    Ok(())
-}
+/// the folders and panels a scan no longer found, and the user whose last read folder may be one of them
+pub type StaleDeletions = (Vec<OsFolder>, Vec<MangaPanel>, User);
 
-#[command]
-pub fn delete_os_folders(
-    handle: AppHandle,
+/// writes everything a scan read in a single transaction, together with removing what it no longer found,
+/// so a failed or cancelled scan never leaves half of a folder in the database
+pub fn save_scanned_entries(
+    handle: &AppHandle,
+    stale: Option<StaleDeletions>,
     os_folders: Vec<OsFolder>,
-    mut user: Option<User>,
+    panels: Vec<MangaPanel>,
+    series_metadata: Vec<SeriesMetadata>,
+    epub_spines: Vec<EpubSpine>,
+    cover_variants: Vec<CoverVariants>,
+    panel_dimensions: Vec<PanelDimensions>,
 ) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+    let updated_at = now_timestamp();
     let app_data_dir = handle.path().app_data_dir()?;
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
-    let rwtx = db.rw_transaction()?;
 
-    for folder in os_folders {
-        // Retrieve all direct child folders
-        let child_folders: Vec<OsFolder> = rwtx
-            .scan()
-            .secondary(OsFolderKey::parent_path)?
-            .start_with(Some(folder.path.as_str()))?
-            .try_collect()?;
+    let mut unused = UnusedFiles::default();
+    if let Some((old_dirs, old_panels, user)) = stale {
+        unused = remove_os_folders(handle, &rwtx, old_dirs, Some(user))?;
+        unused.splits.extend(remove_panels(&rwtx, old_panels)?);
+    }
 
-        // Collect all panels for the current folder and its child folders
-        let panels: Vec<MangaPanel> = rwtx
-            .scan()
+    // a stale folder can release a cover the scan points to again, its files stay
+    let cover_files: HashMap<String, Vec<String>> = cover_variants
+        .iter()
+        .map(|v| {
+            let files = v.variants.iter().map(|variant| variant.path.clone());
+            (v.cover_path.clone(), files.collect())
+        })
+        .collect();
+    let mut retained_files = HashSet::new();
+
+    for mut panel in panels {
+        panel.updated_at = updated_at;
+        rwtx.upsert(panel)?;
+    }
+
+    for m in series_metadata {
+        rwtx.upsert(m)?;
+    }
+
+    for spine in epub_spines {
+        rwtx.upsert(spine)?;
+    }
+
+    for v in cover_variants {
+        rwtx.upsert(v)?;
+    }
+
+    for d in panel_dimensions {
+        rwtx.upsert(d)?;
+    }
+
+    for mut folder in os_folders {
+        let old: Option<OsFolder> = rwtx.get().primary(folder.path.as_str())?;
+        let old_cover = old.and_then(|f| f.cover_img_path);
+        if old_cover != folder.cover_img_path {
+            if let Some(cover) = &old_cover {
+                unused
+                    .covers
+                    .extend(release_cover(&rwtx, &app_data_dir, cover)?);
+            }
+            if let Some(cover) = &folder.cover_img_path {
+                retain_cover(&rwtx, &app_data_dir, cover)?;
+                retained_files.insert(cover.clone());
+                retained_files.extend(cover_files.get(cover).into_iter().flatten().cloned());
+            }
+        }
+
+        folder.updated_at = updated_at;
+        rwtx.upsert(folder)?;
+    }
+
+    rwtx.commit()?;
+    unused.covers.retain(|c| !retained_files.contains(c));
+    unused.remove();
+
+    Ok(())
+}
+
+/// the database no longer points to the covers, so one that can't be removed is only logged
+fn remove_cover_files(covers: Vec<String>) {
+    for cover in covers {
+        if let Err(e) = fs::remove_file(&cover) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("failed to delete cover {cover}: {e}");
+            }
+        }
+    }
+}
+
+fn retain_cover(
+    rwtx: &RwTransaction,
+    app_data_dir: &Path,
+    cover_path: &str,
+) -> Result<(), DatabaseError> {
+    if !is_cached_cover(app_data_dir, cover_path) {
+        return Ok(());
+    }
+
+    let cover: Option<CoverRef> = rwtx.get().primary(cover_path)?;
+    let ref_count = cover.map_or(0, |c| c.ref_count) + 1;
+    rwtx.upsert(CoverRef {
+        path: cover_path.to_string(),
+        ref_count,
+    })?;
+
+    Ok(())
+}
+
+/// returns the files of the cover and its thumbnails once the last folder stops pointing to it
+fn release_cover(
+    rwtx: &RwTransaction,
+    app_data_dir: &Path,
+    cover_path: &str,
+) -> Result<Vec<String>, DatabaseError> {
+    if !is_cached_cover(app_data_dir, cover_path) {
+        return Ok(Vec::new());
+    }
+
+    let Some(mut cover) = rwtx.get().primary::<CoverRef>(cover_path)? else {
+        return Ok(Vec::new());
+    };
+    if cover.ref_count > 1 {
+        cover.ref_count -= 1;
+        rwtx.upsert(cover)?;
+        return Ok(Vec::new());
+    }
+    rwtx.remove(cover)?;
+
+    let mut files = vec![cover_path.to_string()];
+    if let Some(variants) = rwtx.get().primary::<CoverVariants>(cover_path)? {
+        files.extend(
+            variants
+                .variants
+                .iter()
+                .map(|v| v.path.clone())
+                .filter(|p| p != cover_path),
+        );
+        rwtx.remove(variants)?;
+    }
+
+    Ok(files)
+}
+
+/// recounts the references of every cached cover from the `OsFolder`s that point to them.
+/// returns the paths of every cover that is still in use,
+/// including the ones only shown as a user's last read folder.
+pub fn rebuild_cover_refs(handle: &AppHandle) -> Result<HashSet<String>, DatabaseError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    let mut ref_counts: HashMap<String, u32> = HashMap::new();
+    let folders: Vec<OsFolder> = rwtx.scan().primary()?.all()?.try_collect()?;
+    let covers = folders
+        .into_iter()
+        .filter_map(|f| f.cover_img_path)
+        .filter(|cover| is_cached_cover(&app_data_dir, cover));
+    for cover in covers {
+        *ref_counts.entry(cover).or_default() += 1;
+    }
+
+    let old_refs: Vec<CoverRef> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for cover in old_refs {
+        rwtx.remove(cover)?;
+    }
+    let variants: Vec<CoverVariants> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for v in variants {
+        if !ref_counts.contains_key(&v.cover_path) {
+            rwtx.remove(v)?;
+        }
+    }
+    for (path, ref_count) in &ref_counts {
+        rwtx.insert(CoverRef {
+            path: path.clone(),
+            ref_count: *ref_count,
+        })?;
+    }
+    rwtx.commit()?;
+
+    let users: Vec<User> = db.read()?.scan().primary()?.all()?.try_collect()?;
+    let in_use = ref_counts
+        .into_keys()
+        .chain(
+            users
+                .into_iter()
+                .filter_map(|u| u.last_read_manga_folder?.cover_img_path),
+        )
+        .collect();
+
+    Ok(in_use)
+}
+
+impl ThumbnailSettings {
+    pub fn new(user_id: String) -> Self {
+        ThumbnailSettings {
+            user_id,
+            format: ThumbnailFormat::default(),
+            quality: ThumbnailQuality::default(),
+            sizes: vec![
+                ThumbnailSize::Grid,
+                ThumbnailSize::Detail,
+                ThumbnailSize::Full,
+            ],
+            updated_at: now_timestamp(),
+        }
+    }
+}
+
+/// the user's thumbnail settings, or the defaults if they were never changed
+#[command]
+pub fn get_thumbnail_settings(
+    handle: AppHandle,
+    user_id: String,
+) -> Result<ThumbnailSettings, DatabaseError> {
+    let settings: Option<ThumbnailSettings> = handle.repo().get(user_id.as_str())?;
+
+    Ok(settings.unwrap_or_else(|| ThumbnailSettings::new(user_id)))
+}
+
+/// only applies to covers compressed from now on,
+/// the old ones are replaced the next time their folder is read
+#[command]
+pub fn update_thumbnail_settings(
+    handle: AppHandle,
+    mut settings: ThumbnailSettings,
+) -> Result<(), DatabaseError> {
+    settings.updated_at = now_timestamp();
+
+    handle.repo().upsert(settings)?;
+
+    Ok(())
+}
+
+/// the generated thumbnails of every cover, so the frontend can pick the smallest one that fits
+#[command]
+pub fn get_cover_variants(
+    handle: AppHandle,
+    cover_paths: Vec<String>,
+) -> Result<Vec<CoverVariants>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let mut variants = Vec::new();
+    for path in cover_paths {
+        let v: Option<CoverVariants> = rtx.get().primary(path.as_str())?;
+        variants.extend(v);
+    }
+
+    Ok(variants)
+}
+
+/// every folder that was added to the library, for all users
+pub fn get_root_os_folders(handle: &AppHandle) -> Result<Vec<OsFolder>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let folders: Vec<OsFolder> = rtx
+        .scan()
+        .primary()?
+        .all()?
+        .filter_map(|result| result.ok())
+        .filter(|folder: &OsFolder| folder.parent_path.is_none())
+        .collect();
+
+    Ok(folders)
+}
+
+/// swaps the `old_prefix` of a path for `new_prefix`,
+/// or returns `None` if the path isn't inside of `old_prefix`
+fn rewrite_path_prefix(path: &str, old_prefix: &str, new_prefix: &str) -> Option<String> {
+    let rest = Path::new(path).strip_prefix(old_prefix).ok()?;
+    let new_path = match rest.as_os_str().is_empty() {
+        true => PathBuf::from(new_prefix),
+        false => Path::new(new_prefix).join(rest),
+    };
+    Some(new_path.to_string_lossy().to_string())
+}
+
+fn relocate_panel(panel: &mut MangaPanel, old_prefix: &str, new_prefix: &str) -> bool {
+    let Some(path) = rewrite_path_prefix(&panel.path, old_prefix, new_prefix) else {
+        return false;
+    };
+    panel.path = path;
+    if let Some(parent_path) = rewrite_path_prefix(&panel.parent_path, old_prefix, new_prefix) {
+        panel.parent_path = parent_path;
+    }
+    true
+}
+
+fn relocate_os_folder(folder: &mut OsFolder, old_prefix: &str, new_prefix: &str) -> bool {
+    let Some(path) = rewrite_path_prefix(&folder.path, old_prefix, new_prefix) else {
+        return false;
+    };
+    folder.path = path;
+
+    let rewrite = |p: &mut Option<String>| {
+        if let Some(new_path) = p
+            .as_deref()
+            .and_then(|p| rewrite_path_prefix(p, old_prefix, new_prefix))
+        {
+            *p = Some(new_path);
+        }
+    };
+    rewrite(&mut folder.parent_path);
+    // compressed covers live in the app data dir, only uncompressed ones point into the library
+    rewrite(&mut folder.cover_img_path);
+
+    if let Some(panel) = folder.last_read_panel.as_mut() {
+        relocate_panel(panel, old_prefix, new_prefix);
+    }
+    true
+}
+
+/// the new path can already have been scanned before the library was relocated,
+/// so whichever of the two entries was updated last is kept. returns the one that's dropped.
+fn merge_relocated<T>(
+    rwtx: &RwTransaction,
+    relocated: T,
+    path: &str,
+) -> Result<Option<T>, DatabaseError>
+where
+    T: ToInput + HasTimestamp,
+{
+    let existing: Option<T> = rwtx.get().primary(path)?;
+    match existing {
+        Some(existing) if existing.updated_at() >= relocated.updated_at() => Ok(Some(relocated)),
+        existing => {
+            rwtx.upsert(relocated)?;
+            Ok(existing)
+        }
+    }
+}
+
+/// moves every folder, panel and ComicInfo under `old_prefix` to `new_prefix`,
+/// for example when a drive letter changes. read progress is kept.
+/// returns the amount of folders that were moved.
+#[command]
+pub async fn relocate_library(
+    handle: AppHandle,
+    old_prefix: String,
+    new_prefix: String,
+) -> Result<usize, DatabaseError> {
+    if !Path::new(&new_prefix).exists() {
+        return Err(DatabaseError::RelocateTargetNotFound(new_prefix));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || move_library(&handle, &old_prefix, &new_prefix))
+        .await?
+}
+
+fn move_library(
+    handle: &AppHandle,
+    old_prefix: &str,
+    new_prefix: &str,
+) -> Result<usize, DatabaseError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    // the paths are primary and secondary keys,
+    // so every entry has to be removed and written again under its new path
+    let folders: Vec<OsFolder> = rwtx.scan().primary()?.all()?.try_collect()?;
+    let mut old_roots = Vec::new();
+    let mut new_roots = Vec::new();
+    let mut unused_covers = Vec::new();
+    let mut relocated = 0;
+    for folder in folders {
+        let mut new_folder = folder.clone();
+        if !relocate_os_folder(&mut new_folder, old_prefix, new_prefix) {
+            continue;
+        }
+        if folder.parent_path.is_none() {
+            old_roots.push(folder.path.clone());
+            new_roots.push(new_folder.path.clone());
+        }
+        rwtx.remove(folder)?;
+        let path = new_folder.path.clone();
+        let dropped = merge_relocated(&rwtx, new_folder, &path)?;
+        if let Some(cover) = dropped.and_then(|f| f.cover_img_path) {
+            unused_covers.extend(release_cover(&rwtx, &app_data_dir, &cover)?);
+        }
+        relocated += 1;
+    }
+
+    let panels: Vec<MangaPanel> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for panel in panels {
+        let mut new_panel = panel.clone();
+        if relocate_panel(&mut new_panel, old_prefix, new_prefix) {
+            rwtx.remove(panel)?;
+            let path = new_panel.path.clone();
+            merge_relocated(&rwtx, new_panel, &path)?;
+        }
+    }
+
+    let dimensions: Vec<PanelDimensions> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for d in dimensions {
+        if let Some(path) = rewrite_path_prefix(&d.path, old_prefix, new_prefix) {
+            let mut new_d = d.clone();
+            new_d.path = path;
+            if let Some(parent_path) = rewrite_path_prefix(&d.parent_path, old_prefix, new_prefix) {
+                new_d.parent_path = parent_path;
+            }
+            rwtx.remove(d)?;
+            rwtx.upsert(new_d)?;
+        }
+    }
+
+    let series_metadata: Vec<SeriesMetadata> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for metadata in series_metadata {
+        if let Some(path) = rewrite_path_prefix(&metadata.folder_path, old_prefix, new_prefix) {
+            let mut new_metadata = metadata.clone();
+            new_metadata.folder_path = path;
+            rwtx.remove(metadata)?;
+            rwtx.upsert(new_metadata)?;
+        }
+    }
+
+    let spines: Vec<EpubSpine> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for spine in spines {
+        if let Some(path) = rewrite_path_prefix(&spine.folder_path, old_prefix, new_prefix) {
+            let mut new_spine = spine.clone();
+            new_spine.folder_path = path;
+            for page in new_spine.pages.iter_mut() {
+                if let Some(new_page) = rewrite_path_prefix(page, old_prefix, new_prefix) {
+                    *page = new_page;
+                }
+            }
+            rwtx.remove(spine)?;
+            rwtx.upsert(new_spine)?;
+        }
+    }
+
+    // the split halves live in the app data dir, only the source they point to moves
+    let mut unused_splits = Vec::new();
+    let splits: Vec<SplitPanel> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for split in splits {
+        let mut new_split = split.clone();
+        if relocate_panel(&mut new_split.source, old_prefix, new_prefix) {
+            new_split.source_path = new_split.source.path.clone();
+            new_split.parent_path = new_split.source.parent_path.clone();
+            rwtx.remove(split)?;
+            let existing: Option<SplitPanel> =
+                rwtx.get().primary(new_split.source_path.as_str())?;
+            if let Some(existing) = existing {
+                unused_splits.extend(remove_split_panel(&rwtx, existing)?);
+            }
+            rwtx.upsert(new_split)?;
+        }
+    }
+
+    let split_spreads: Vec<SplitSpreads> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for split_spreads in split_spreads {
+        if let Some(path) = rewrite_path_prefix(&split_spreads.folder_path, old_prefix, new_prefix)
+        {
+            let mut new_split_spreads = split_spreads.clone();
+            new_split_spreads.folder_path = path;
+            rwtx.remove(split_spreads)?;
+            rwtx.upsert(new_split_spreads)?;
+        }
+    }
+
+    let folder_crops: Vec<FolderCrop> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for folder_crop in folder_crops {
+        if let Some(path) = rewrite_path_prefix(&folder_crop.folder_path, old_prefix, new_prefix) {
+            let mut new_folder_crop = folder_crop.clone();
+            new_folder_crop.folder_path = path;
+            rwtx.remove(folder_crop)?;
+            rwtx.upsert(new_folder_crop)?;
+        }
+    }
+
+    let reading_profiles: Vec<FolderReadingProfile> =
+        rwtx.scan().primary()?.all()?.try_collect()?;
+    for reading_profile in reading_profiles {
+        if let Some(path) =
+            rewrite_path_prefix(&reading_profile.folder_path, old_prefix, new_prefix)
+        {
+            let mut new_reading_profile = reading_profile.clone();
+            new_reading_profile.folder_path = path;
+            rwtx.remove(reading_profile)?;
+            rwtx.upsert(new_reading_profile)?;
+        }
+    }
+
+    // the fingerprint of a chapter's crop is made from its panel paths,
+    // so it's detected again under the new path instead of being moved
+    let chapter_crops: Vec<ChapterCrop> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for chapter_crop in chapter_crops {
+        if rewrite_path_prefix(&chapter_crop.folder_path, old_prefix, new_prefix).is_some() {
+            rwtx.remove(chapter_crop)?;
+        }
+    }
+
+    // the tiles are cut again under the new path, like the crop of a chapter
+    let mut unused_strips = Vec::new();
+    let strips: Vec<WebtoonStrip> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for strip in strips {
+        if rewrite_path_prefix(&strip.folder_path, old_prefix, new_prefix).is_some() {
+            unused_strips.push(strip.dir.clone());
+            rwtx.remove(strip)?;
+        }
+    }
+
+    // the new location exists, so a relocated root is online again
+    let roots: Vec<LibraryRoot> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for root in roots {
+        if let Some(path) = rewrite_path_prefix(&root.path, old_prefix, new_prefix) {
+            let mut new_root = root.clone();
+            new_root.path = path;
+            new_root.is_offline = false;
+            rwtx.remove(root)?;
+            rwtx.upsert(new_root)?;
+        }
+    }
+
+    let users: Vec<User> = rwtx.scan().primary()?.all()?.try_collect()?;
+    for mut user in users {
+        if let Some(folder) = user.last_read_manga_folder.as_mut() {
+            if relocate_os_folder(folder, old_prefix, new_prefix) {
+                rwtx.upsert(user)?;
+            }
+        }
+    }
+
+    rwtx.commit()?;
+
+    remove_split_files(unused_splits);
+    remove_webtoon_dirs(unused_strips);
+    remove_cover_files(unused_covers);
+
+    for root in &old_roots {
+        unwatch_library_root(handle, root);
+    }
+    for root in &new_roots {
+        watch_library_root(handle, root);
+    }
+
+    Ok(relocated)
+}
+
+/// walks up from the path to the root folder it was added to the library with
+pub fn find_library_root(handle: &AppHandle, path: &str) -> Result<Option<String>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    for ancestor in Path::new(path).ancestors() {
+        let folder: Option<OsFolder> = rtx.get().primary(ancestor.to_string_lossy().as_ref())?;
+        if let Some(folder) = folder.filter(|f| f.parent_path.is_none()) {
+            return Ok(Some(folder.path));
+        }
+    }
+
+    Ok(None)
+}
+
+/// flags a library root as offline or back online.
+/// roots without an entry are online, so nothing gets written until a drive goes missing.
+pub fn set_library_root_offline(
+    handle: &AppHandle,
+    root_path: &str,
+    user_id: &str,
+    is_offline: bool,
+) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+
+    let rwtx = db.write()?;
+    let root: Option<LibraryRoot> = rwtx.get().primary(root_path)?;
+    if root.as_ref().is_some_and(|r| r.is_offline) == is_offline {
+        return Ok(());
+    }
+    rwtx.upsert(LibraryRoot {
+        path: root_path.to_string(),
+        user_id: user_id.to_string(),
+        is_offline,
+        updated_at: now_timestamp(),
+    })?;
+    rwtx.commit()?;
+
+    Ok(())
+}
+
+/// every library root of the user that has been offline at some point,
+/// with `is_offline` set if its drive is still missing
+#[command]
+pub fn get_library_roots(
+    handle: AppHandle,
+    user_id: String,
+) -> Result<Vec<LibraryRoot>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let roots: Vec<LibraryRoot> = rtx
+        .scan()
+        .secondary(LibraryRootKey::user_id)?
+        .start_with(user_id.as_str())?
+        .filter_map(|result| result.ok())
+        .filter(|root: &LibraryRoot| root.user_id == user_id)
+        .collect();
+
+    Ok(roots)
+}
+
+/// walks up from the path to the closest folder in the library that still exists on disk
+pub fn find_nearest_os_folder(
+    handle: &AppHandle,
+    path: &Path,
+) -> Result<Option<OsFolder>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    for ancestor in path.ancestors() {
+        let folder: Option<OsFolder> = rtx.get().primary(ancestor.to_string_lossy().as_ref())?;
+        if let Some(folder) = folder {
+            if ancestor.exists() {
+                return Ok(Some(folder));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// the direct child folders and panels of a folder
+pub fn get_os_folder_children(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<(Vec<OsFolder>, Vec<MangaPanel>), DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let folders: Vec<OsFolder> = rtx
+        .scan()
+        .secondary(OsFolderKey::parent_path)?
+        .start_with(Some(folder_path))?
+        .filter_map(|result| result.ok())
+        .filter(|folder: &OsFolder| folder.parent_path.as_deref() == Some(folder_path))
+        .collect();
+
+    let panels: Vec<MangaPanel> = rtx
+        .scan()
+        .secondary(MangaPanelKey::parent_path)?
+        .start_with(folder_path)?
+        .filter_map(|result| result.ok())
+        .filter(|panel: &MangaPanel| panel.parent_path == folder_path)
+        .collect();
+
+    Ok((folders, panels))
+}
+
+/// files of removed entries, only deleted once the transaction that removed them committed
+#[derive(Default)]
+struct UnusedFiles {
+    covers: Vec<String>,
+    splits: Vec<String>,
+    strips: Vec<String>,
+}
+
+impl UnusedFiles {
+    fn remove(self) {
+        remove_split_files(self.splits);
+        remove_webtoon_dirs(self.strips);
+
+        // covers are shared between folders, so they are only deleted once nothing points to them
+        remove_cover_files(self.covers);
+    }
+}
+
+/// returns the files of the halves of split panels
+fn remove_panels(
+    rwtx: &RwTransaction,
+    panels: Vec<MangaPanel>,
+) -> Result<Vec<String>, DatabaseError> {
+    let mut unused_splits = Vec::new();
+    for p in panels {
+        let dimensions: Option<PanelDimensions> = rwtx.get().primary(p.path.as_str())?;
+        if let Some(d) = dimensions {
+            rwtx.remove(d)?;
+        }
+        let split: Option<SplitPanel> = rwtx.get().primary(p.path.as_str())?;
+        if let Some(split) = split {
+            unused_splits.extend(remove_split_panel(&rwtx, split)?);
+        }
+        rwtx.remove(p)?;
+    }
+
+    Ok(unused_splits)
+}
+
+#[command]
+pub fn delete_os_folders(
+    handle: AppHandle,
+    os_folders: Vec<OsFolder>,
+    user: Option<User>,
+) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+    let unused = remove_os_folders(&handle, &rwtx, os_folders, user)?;
+    rwtx.commit()?;
+
+    unused.remove();
+
+    Ok(())
+}
+
+/// removes the folders with everything kept for them and their child folders.
+/// the user's last read folder is cleared if it's one of them.
+fn remove_os_folders(
+    handle: &AppHandle,
+    rwtx: &RwTransaction,
+    os_folders: Vec<OsFolder>,
+    mut user: Option<User>,
+) -> Result<UnusedFiles, DatabaseError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+
+    let mut unused_covers = Vec::new();
+    let mut unused_splits = Vec::new();
+    let mut unused_strips = Vec::new();
+
+    for folder in os_folders {
+        // Retrieve all direct child folders
+        let child_folders: Vec<OsFolder> = rwtx
+            .scan()
+            .secondary(OsFolderKey::parent_path)?
+            .start_with(Some(folder.path.as_str()))?
+            .try_collect()?;
+
+        // Collect all panels for the current folder and its child folders
+        let panels: Vec<MangaPanel> = rwtx
+            .scan()
             .secondary(MangaPanelKey::parent_path)?
             .start_with(folder.path.as_str())?
             .try_collect()?;
@@ -640,8 +2068,63 @@ pub fn delete_os_folders(
             rwtx.remove(panel)?;
         }
 
+        let dimensions: Vec<PanelDimensions> = rwtx
+            .scan()
+            .secondary(PanelDimensionsKey::parent_path)?
+            .start_with(folder.path.as_str())?
+            .try_collect()?;
+        for d in dimensions {
+            rwtx.remove(d)?;
+        }
+
+        let splits: Vec<SplitPanel> = rwtx
+            .scan()
+            .secondary(SplitPanelKey::parent_path)?
+            .start_with(folder.path.as_str())?
+            .try_collect()?;
+        for split in splits {
+            unused_splits.extend(remove_split_panel(rwtx, split)?);
+        }
+
+        // Delete the ComicInfo metadata, epub spine, split, crop, reading settings and webtoon tiles of the folder and its child folders
+        for path in child_folders.iter().map(|f| &f.path).chain([&folder.path]) {
+            let metadata: Option<SeriesMetadata> = rwtx.get().primary(path.as_str())?;
+            if let Some(metadata) = metadata {
+                rwtx.remove(metadata)?;
+            }
+            let spine: Option<EpubSpine> = rwtx.get().primary(path.as_str())?;
+            if let Some(spine) = spine {
+                rwtx.remove(spine)?;
+            }
+            let split_spreads: Option<SplitSpreads> = rwtx.get().primary(path.as_str())?;
+            if let Some(split_spreads) = split_spreads {
+                rwtx.remove(split_spreads)?;
+            }
+            let folder_crop: Option<FolderCrop> = rwtx.get().primary(path.as_str())?;
+            if let Some(folder_crop) = folder_crop {
+                rwtx.remove(folder_crop)?;
+            }
+            let chapter_crop: Option<ChapterCrop> = rwtx.get().primary(path.as_str())?;
+            if let Some(chapter_crop) = chapter_crop {
+                rwtx.remove(chapter_crop)?;
+            }
+            let reading_profile: Option<FolderReadingProfile> =
+                rwtx.get().primary(path.as_str())?;
+            if let Some(reading_profile) = reading_profile {
+                rwtx.remove(reading_profile)?;
+            }
+            let strip: Option<WebtoonStrip> = rwtx.get().primary(path.as_str())?;
+            if let Some(strip) = strip {
+                unused_strips.push(strip.dir.clone());
+                rwtx.remove(strip)?;
+            }
+        }
+
         // Delete all child folders
         for f in child_folders {
+            if let Some(cover) = &f.cover_img_path {
+                unused_covers.extend(release_cover(rwtx, &app_data_dir, cover)?);
+            }
             if let Some(ref mut user) = user {
                 if let Some(lrmf) = &user.last_read_manga_folder {
                     if f.path == lrmf.path {
@@ -654,9 +2137,9 @@ pub fn delete_os_folders(
             rwtx.remove(f)?;
         }
 
-        folder
-            .delete_app_data_cover_folder(&app_data_dir)
-            .map_err(|e| DatabaseError::DeleteCoverFolder(folder.path.clone(), e.to_string()))?;
+        if let Some(cover) = &folder.cover_img_path {
+            unused_covers.extend(release_cover(rwtx, &app_data_dir, cover)?);
+        }
 
         if let Some(ref mut user) = user {
             if let Some(lrmf) = &user.last_read_manga_folder {
@@ -667,6 +2150,14 @@ pub fn delete_os_folders(
             }
         }
 
+        if folder.parent_path.is_none() {
+            unwatch_library_root(handle, &folder.path);
+            let root: Option<LibraryRoot> = rwtx.get().primary(folder.path.as_str())?;
+            if let Some(root) = root {
+                rwtx.remove(root)?;
+            }
+        }
+
         // Finally, delete the folder itself
         rwtx.remove(folder)?;
     }
@@ -675,41 +2166,82 @@ pub fn delete_os_folders(
         //dbg!(&user);
         rwtx.upsert(user)?;
     }
-    rwtx.commit()?;
+
+    Ok(UnusedFiles {
+        covers: unused_covers,
+        splits: unused_splits,
+        strips: unused_strips,
+    })
+}
+
+pub fn update_series_metadata(
+    handle: &AppHandle,
+    metadata: Vec<SeriesMetadata>,
+) -> Result<(), DatabaseError> {
+    handle.repo().upsert_all(metadata)?;
 
     Ok(())
 }
 
 #[command]
-pub fn get_default_user(handle: AppHandle) -> Result<User, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
+pub fn get_series_metadata(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<SeriesMetadata, DatabaseError> {
+    let metadata: Option<SeriesMetadata> = handle.repo().get(folder_path.as_str())?;
 
-    let rtx = db.r_transaction()?;
-    let user: Option<User> = rtx.get().primary("1")?;
+    metadata.ok_or_else(|| {
+        DatabaseError::SeriesMetadataNotFound(format!("no ComicInfo found for: {folder_path}"))
+    })
+}
+
+/// case-insensitive search over the series, title, writers, pencillers and genres
+#[command]
+pub fn search_series_metadata(
+    handle: AppHandle,
+    user_id: String,
+    query: String,
+) -> Result<Vec<SeriesMetadata>, DatabaseError> {
+    let db = handle.repo();
+
+    let query = query.to_lowercase();
+    let matches = |s: &String| s.to_lowercase().contains(&query);
+
+    let rtx = db.read()?;
+    let results: Vec<SeriesMetadata> = rtx
+        .scan()
+        .secondary(SeriesMetadataKey::user_id)?
+        .start_with(user_id.as_str())?
+        .filter_map(|result| result.ok())
+        .filter(|m: &SeriesMetadata| {
+            m.series.iter().chain(m.title.iter()).any(matches)
+                || m.writers.iter().any(matches)
+                || m.pencillers.iter().any(matches)
+                || m.genres.iter().any(matches)
+        })
+        .collect();
+
+    Ok(results)
+}
+
+#[command]
+pub fn get_default_user(handle: AppHandle) -> Result<User, DatabaseError> {
+    let user: Option<User> = handle.repo().get("1")?;
 
     user.ok_or_else(|| DatabaseError::UserNotFound(String::from("User with ID 1 not found.")))
 }
 
 #[command]
 pub fn get_user_by_id(handle: AppHandle, user_id: String) -> Result<User, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().create(&DBMODELS, db_path)?;
-
-    let rtx = db.r_transaction()?;
-    let user: Option<User> = rtx.get().primary(user_id.as_str())?;
+    let user: Option<User> = handle.repo().get(user_id.as_str())?;
 
     user.ok_or_else(|| DatabaseError::UserNotFound(format!("User with ID {user_id} not found.")))
 }
 
 #[command]
-pub fn update_user(user: User, handle: AppHandle) -> Result<(), DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
-
-    let rtx = db.rw_transaction()?;
-    rtx.upsert(user)?;
-    rtx.commit()?;
+pub fn update_user(mut user: User, handle: AppHandle) -> Result<(), DatabaseError> {
+    user.settings.updated_at = now_timestamp();
+    handle.repo().upsert(user)?;
 
     Ok(())
 }
@@ -720,10 +2252,9 @@ pub fn get_prev_folder(
     parent_path: String,
     current_folder: OsFolder,
 ) -> Result<OsFolder, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let mut folders: Vec<OsFolder> = rtx
         .scan()
         .secondary(OsFolderKey::parent_path)?
@@ -736,7 +2267,7 @@ pub fn get_prev_folder(
         )));
     }
 
-    folders.par_sort_by(SortType::sort(&SortType::EpisodeTitleRegex));
+    SortType::Natural.par_sort(&mut folders);
 
     match folders
         .into_iter()
@@ -757,10 +2288,9 @@ pub fn get_next_folder(
     parent_path: String,
     current_folder: OsFolder,
 ) -> Result<OsFolder, DatabaseError> {
-    let db_path = handle.state::<PathBuf>().to_string_lossy().to_string();
-    let db = Builder::new().open(&DBMODELS, db_path)?;
+    let db = handle.repo();
 
-    let rtx = db.r_transaction()?;
+    let rtx = db.read()?;
     let mut folders: Vec<OsFolder> = rtx
         .scan()
         .secondary(OsFolderKey::parent_path)?
@@ -773,7 +2303,7 @@ pub fn get_next_folder(
         )));
     }
 
-    folders.par_sort_by(SortType::sort(&SortType::EpisodeTitleRegex));
+    SortType::Natural.par_sort(&mut folders);
 
     match folders
         .into_iter()
@@ -786,3 +2316,272 @@ pub fn get_next_folder(
         ))),
     }
 }
+
+/// the dimensions of every panel in the folder that has been measured
+pub fn get_panel_dimensions(
+    handle: &AppHandle,
+    parent_path: &str,
+) -> Result<Vec<PanelDimensions>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let dimensions: Vec<PanelDimensions> = rtx
+        .scan()
+        .secondary(PanelDimensionsKey::parent_path)?
+        .start_with(parent_path)?
+        .take_while(|d: &Result<PanelDimensions, db_type::Error>| match d {
+            Ok(d) => d.parent_path == parent_path,
+            Err(_) => false,
+        })
+        .try_collect()?;
+
+    Ok(dimensions)
+}
+
+pub fn save_panel_dimensions(
+    handle: &AppHandle,
+    dimensions: Vec<PanelDimensions>,
+) -> Result<(), DatabaseError> {
+    handle.repo().upsert_all(dimensions)?;
+
+    Ok(())
+}
+
+pub fn get_split_spreads(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<Option<SplitSpreads>, DatabaseError> {
+    Ok(handle.repo().get(folder_path)?)
+}
+
+/// saves the folder's split settings. turning splitting off drops the halves that were cached.
+pub fn save_split_spreads(
+    handle: &AppHandle,
+    split_spreads: SplitSpreads,
+) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    let mut unused_splits = Vec::new();
+    if !split_spreads.is_enabled {
+        let splits: Vec<SplitPanel> = rwtx
+            .scan()
+            .secondary(SplitPanelKey::parent_path)?
+            .start_with(split_spreads.folder_path.as_str())?
+            .take_while(|s: &Result<SplitPanel, db_type::Error>| match s {
+                Ok(s) => s.parent_path == split_spreads.folder_path,
+                Err(_) => false,
+            })
+            .try_collect()?;
+        for split in splits {
+            unused_splits.extend(remove_split_panel(&rwtx, split)?);
+        }
+    }
+
+    rwtx.upsert(split_spreads)?;
+    rwtx.commit()?;
+
+    remove_split_files(unused_splits);
+
+    Ok(())
+}
+
+/// the panels of the folder that have been split
+pub fn get_split_panels(
+    handle: &AppHandle,
+    parent_path: &str,
+) -> Result<Vec<SplitPanel>, DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let splits: Vec<SplitPanel> = rtx
+        .scan()
+        .secondary(SplitPanelKey::parent_path)?
+        .start_with(parent_path)?
+        .take_while(|s: &Result<SplitPanel, db_type::Error>| match s {
+            Ok(s) => s.parent_path == parent_path,
+            Err(_) => false,
+        })
+        .try_collect()?;
+
+    Ok(splits)
+}
+
+/// a panel that got split again keeps the paths of its halves,
+/// so the dimensions measured for the old halves are dropped
+pub fn save_split_panels(handle: &AppHandle, splits: Vec<SplitPanel>) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    for split in splits {
+        for half in [&split.left, &split.right] {
+            let dimensions: Option<PanelDimensions> = rwtx.get().primary(half.as_str())?;
+            if let Some(d) = dimensions {
+                rwtx.remove(d)?;
+            }
+        }
+        rwtx.upsert(split)?;
+    }
+
+    rwtx.commit()?;
+
+    Ok(())
+}
+
+/// removes the split and the dimensions of its halves, and returns the files of the halves
+fn remove_split_panel(
+    rwtx: &RwTransaction,
+    split: SplitPanel,
+) -> Result<[String; 2], DatabaseError> {
+    for half in [&split.left, &split.right] {
+        let dimensions: Option<PanelDimensions> = rwtx.get().primary(half.as_str())?;
+        if let Some(d) = dimensions {
+            rwtx.remove(d)?;
+        }
+    }
+
+    let files = [split.left.clone(), split.right.clone()];
+    rwtx.remove(split)?;
+    Ok(files)
+}
+
+/// the halves are only a cache, so a file that can't be removed is left behind
+pub fn remove_split_files(files: Vec<String>) {
+    for file in files {
+        if let Err(e) = fs::remove_file(&file) {
+            eprintln!("failed to delete split panel {file}: {e}");
+        }
+    }
+}
+
+pub fn get_folder_crop(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<Option<FolderCrop>, DatabaseError> {
+    Ok(handle.repo().get(folder_path)?)
+}
+
+/// `None` drops the folder's override, so it follows the user's default again
+pub fn save_folder_crop(
+    handle: &AppHandle,
+    folder_path: &str,
+    folder_crop: Option<FolderCrop>,
+) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    match folder_crop {
+        Some(folder_crop) => {
+            rwtx.upsert(folder_crop)?;
+        }
+        None => {
+            let old: Option<FolderCrop> = rwtx.get().primary(folder_path)?;
+            if let Some(old) = old {
+                rwtx.remove(old)?;
+            }
+        }
+    }
+
+    rwtx.commit()?;
+
+    Ok(())
+}
+
+pub fn get_chapter_crop(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<Option<ChapterCrop>, DatabaseError> {
+    Ok(handle.repo().get(folder_path)?)
+}
+
+pub fn save_chapter_crop(
+    handle: &AppHandle,
+    chapter_crop: ChapterCrop,
+) -> Result<(), DatabaseError> {
+    handle.repo().upsert(chapter_crop)?;
+
+    Ok(())
+}
+
+pub fn get_folder_reading_profile(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<Option<FolderReadingProfile>, DatabaseError> {
+    Ok(handle.repo().get(folder_path)?)
+}
+
+/// `None` drops the folder's profile, so it inherits everything again
+pub fn save_folder_reading_profile(
+    handle: &AppHandle,
+    folder_path: &str,
+    reading_profile: Option<FolderReadingProfile>,
+) -> Result<(), DatabaseError> {
+    let db = handle.repo();
+    let rwtx = db.write()?;
+
+    match reading_profile {
+        Some(reading_profile) => {
+            rwtx.upsert(reading_profile)?;
+        }
+        None => {
+            let old: Option<FolderReadingProfile> = rwtx.get().primary(folder_path)?;
+            if let Some(old) = old {
+                rwtx.remove(old)?;
+            }
+        }
+    }
+
+    rwtx.commit()?;
+
+    Ok(())
+}
+
+/// the profiles set on the folder and the folders above it, closest first,
+/// with the user the folder belongs to
+pub fn get_inherited_reading_profiles(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<(Option<String>, Vec<ReadingProfileOverrides>), DatabaseError> {
+    let db = handle.repo();
+
+    let rtx = db.read()?;
+    let mut user_id = None;
+    let mut profiles = Vec::new();
+    let mut path = Some(folder_path.to_string());
+    while let Some(current) = path.take() {
+        let profile: Option<FolderReadingProfile> = rtx.get().primary(current.as_str())?;
+        profiles.extend(profile.map(|p| p.profile));
+
+        let folder: Option<OsFolder> = rtx.get().primary(current.as_str())?;
+        if let Some(folder) = folder {
+            user_id.get_or_insert(folder.user_id);
+            path = folder.parent_path;
+        }
+    }
+
+    Ok((user_id, profiles))
+}
+
+pub fn get_webtoon_strip(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<Option<WebtoonStrip>, DatabaseError> {
+    Ok(handle.repo().get(folder_path)?)
+}
+
+pub fn save_webtoon_strip(handle: &AppHandle, strip: WebtoonStrip) -> Result<(), DatabaseError> {
+    handle.repo().upsert(strip)?;
+
+    Ok(())
+}
+
+/// the tiles are only a cache, so a dir that can't be removed is left behind
+pub fn remove_webtoon_dirs(dirs: Vec<String>) {
+    for dir in dirs {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("failed to delete webtoon tiles {dir}: {e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{borrow::Cow, io::Read, path::Path};
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{archive::open_zip, error::ArchiveError};
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// the parts of a fixed-layout epub that the scanner cares about
+#[derive(Debug, Clone, Default)]
+pub struct EpubPackage {
+    /// `dc:title` from the opf metadata
+    pub title: Option<String>,
+    /// image entry names, in spine (reading) order
+    pub pages: Vec<String>,
+}
+
+/// parses the opf spine of an epub and resolves every spine item to the image it displays.
+/// spine items can either be images themselves, or xhtml pages wrapping a single `<img>`/`<svg><image>`.
+pub fn read_epub_package(archive: impl AsRef<Path>) -> Result<EpubPackage, ArchiveError> {
+    let mut zip = open_zip(archive.as_ref())?;
+
+    parse_package(|name| {
+        let mut file = zip.by_name(name)?;
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        Ok(buf)
+    })
+}
+
+/// `read_entry` reads an entry of the epub by its name
+fn parse_package(
+    mut read_entry: impl FnMut(&str) -> Result<String, ArchiveError>,
+) -> Result<EpubPackage, ArchiveError> {
+    let container = read_entry(CONTAINER_PATH)?;
+    let opf_path = find_first_attr(&container, b"rootfile", b"full-path")?.ok_or_else(|| {
+        ArchiveError::InvalidEpub(format!("{CONTAINER_PATH} is missing a rootfile"))
+    })?;
+    let opf_dir = parent_dir(&opf_path);
+    let opf = read_entry(&opf_path)?;
+
+    let mut title = None;
+    let mut in_title = false;
+    // (id, href, media-type)
+    let mut manifest: Vec<(String, String, String)> = Vec::new();
+    let mut spine: Vec<String> = Vec::new();
+
+    let mut reader = Reader::from_str(&opf);
+    reader.config_mut().trim_text(true);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) if e.local_name().as_ref() == b"title" => {
+                in_title = title.is_none();
+            }
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (attr(&e, b"id"), attr(&e, b"href")) {
+                        let media_type = attr(&e, b"media-type").unwrap_or_default();
+                        manifest.push((id, resolve_href(opf_dir, &href), media_type));
+                    }
+                }
+                b"itemref" => {
+                    if let Some(idref) = attr(&e, b"idref") {
+                        spine.push(idref);
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(t) if in_title => {
+                let text = t.unescape().map_err(xml_err)?;
+                title = Some(text.trim().to_string());
+            }
+            Event::End(e) if e.local_name().as_ref() == b"title" => in_title = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut pages = Vec::with_capacity(spine.len());
+    for idref in spine {
+        let Some((_, href, media_type)) = manifest.iter().find(|(id, ..)| *id == idref) else {
+            continue;
+        };
+
+        if media_type.starts_with("image/") {
+            pages.push(href.clone());
+            continue;
+        }
+
+        // fixed-layout pages are xhtml documents that wrap one image
+        let xhtml = match read_entry(href) {
+            Ok(xhtml) => xhtml,
+            Err(e) => {
+                eprintln!("skipping epub spine item {href}: {e}");
+                continue;
+            }
+        };
+        let img_src = match find_first_attr(&xhtml, b"img", b"src")? {
+            Some(src) => Some(src),
+            None => find_first_attr(&xhtml, b"image", b"href")?,
+        };
+        if let Some(src) = img_src {
+            pages.push(resolve_href(parent_dir(href), &src));
+        }
+    }
+
+    Ok(EpubPackage { title, pages })
+}
+
+fn xml_err(e: impl std::fmt::Display) -> ArchiveError {
+    ArchiveError::InvalidEpub(e.to_string())
+}
+
+/// gets an attribute by its local name, so `xlink:href` matches `href`
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(Cow::into_owned))
+}
+
+fn find_first_attr(xml: &str, tag: &[u8], name: &[u8]) -> Result<Option<String>, ArchiveError> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == tag => {
+                if let Some(value) = attr(&e, name) {
+                    return Ok(Some(value));
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn parent_dir(entry: &str) -> &str {
+    entry.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// resolves an href relative to the directory of the document it came from
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode(href);
+
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|s| !s.is_empty()).collect();
+    for seg in href.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            seg => parts.push(seg),
+        }
+    }
+
+    parts.join("/")
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashMap;
+
+    use super::*;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    fn parse(entries: &[(&str, &str)]) -> EpubPackage {
+        let entries: HashMap<&str, &str> = entries.iter().copied().collect();
+        parse_package(|name| {
+            entries
+                .get(name)
+                .map(|xml| xml.to_string())
+                .ok_or_else(|| ArchiveError::EntryNotFound(name.to_string()))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn follows_the_spine_order() {
+        let opf = r#"<package xmlns="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <metadata><dc:title>Vol 1</dc:title></metadata>
+  <manifest>
+    <item id="p1" href="images/001.jpg" media-type="image/jpeg"/>
+    <item id="p2" href="images/002.jpg" media-type="image/jpeg"/>
+    <item id="p3" href="images/003%20b.png" media-type="image/png"/>
+  </manifest>
+  <spine>
+    <itemref idref="p3"/>
+    <itemref idref="p1"/>
+    <itemref idref="missing"/>
+    <itemref idref="p2"/>
+  </spine>
+</package>"#;
+        let pkg = parse(&[(CONTAINER_PATH, CONTAINER), ("OEBPS/content.opf", opf)]);
+
+        assert_eq!(pkg.title.as_deref(), Some("Vol 1"));
+        assert_eq!(
+            pkg.pages,
+            [
+                "OEBPS/images/003 b.png",
+                "OEBPS/images/001.jpg",
+                "OEBPS/images/002.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_xhtml_pages_to_their_images() {
+        let opf = r#"<package xmlns="http://www.idpf.org/2007/opf">
+  <manifest>
+    <item id="cover" href="text/cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="page" href="text/page.xhtml" media-type="application/xhtml+xml"/>
+    <item id="gone" href="text/gone.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="cover"/>
+    <itemref idref="gone"/>
+    <itemref idref="page"/>
+  </spine>
+</package>"#;
+        let cover = r#"<html><body><svg xmlns:xlink="http://www.w3.org/1999/xlink">
+  <image xlink:href="../images/cover.jpg"/>
+</svg></body></html>"#;
+        let page = r#"<html><body><img src="../images/001.jpg#page"/></body></html>"#;
+        let pkg = parse(&[
+            (CONTAINER_PATH, CONTAINER),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/text/cover.xhtml", cover),
+            ("OEBPS/text/page.xhtml", page),
+        ]);
+
+        assert_eq!(pkg.title, None);
+        assert_eq!(
+            pkg.pages,
+            ["OEBPS/images/cover.jpg", "OEBPS/images/001.jpg"]
+        );
+    }
+}
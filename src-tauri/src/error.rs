@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, path::PathBuf};
 
 use native_db::db_type::Error;
 use tauri::ipc::InvokeError;
@@ -9,6 +9,20 @@ pub enum InitError {
     Tuari(#[from] tauri::Error),
     #[error("{0:#?}")]
     Io(#[from] io::Error),
+    #[error("failed to open the database: {0}")]
+    Database(#[from] Error),
+    #[error("failed to back up {} before migrating it: {source}", path.display())]
+    Backup { path: PathBuf, source: io::Error },
+    #[error(
+        "failed to migrate the database to v{version}, it was left as it was. \
+        a copy from before the migration is at {}: {source}",
+        backup.display()
+    )]
+    Migration {
+        version: u32,
+        backup: PathBuf,
+        source: Error,
+    },
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -21,6 +35,8 @@ pub enum MangaShelfError {
     Io(#[from] io::Error),
     #[error("{0}")]
     Tuari(#[from] tauri::Error),
+    #[error("{0}")]
+    ComicInfo(#[from] ComicInfoError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -37,10 +53,12 @@ pub enum DatabaseError {
     IoError(#[from] io::Error),
     #[error("{0}")]
     Tuari(#[from] tauri::Error),
-    #[error("failed to delete cover folder for path: {0}; reason: {1}")]
-    DeleteCoverFolder(String, String),
     #[error("{0}")]
     SortType(#[from] SortTypeError),
+    #[error("SeriesMetadata Not Found: {0}")]
+    SeriesMetadataNotFound(String),
+    #[error("can't relocate the library to a path that doesn't exist: {0}")]
+    RelocateTargetNotFound(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -57,6 +75,14 @@ pub enum ReadDirError {
     Image(#[from] image::ImageError),
     #[error("{0}")]
     MangaImage(#[from] MangaImageError),
+    #[error("{0}")]
+    Archive(ArchiveError),
+    #[error("unsupported compression method: {0}")]
+    UnsupportedCompression(String),
+    #[error("invalid ComicInfo.xml: {0}")]
+    ComicInfo(#[from] quick_xml::DeError),
+    #[error("scan of {0} was cancelled")]
+    ScanCancelled(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -69,6 +95,152 @@ pub enum MangaImageError {
     InvalidPixelType(String),
     #[error("{0}")]
     Resize(#[from] fast_image_resize::ResizeError),
+    #[error("{0}")]
+    Archive(#[from] ArchiveError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Zip(zip::result::ZipError),
+    #[error("{0}")]
+    Decoder(String),
+    #[error("archive format is not supported or was not compiled in: {0}")]
+    UnsupportedFormat(String),
+    #[error("unsupported compression method: {0}")]
+    UnsupportedCompression(String),
+    #[error("entry not found in archive: {0}")]
+    EntryNotFound(String),
+    #[error("invalid epub: {0}")]
+    InvalidEpub(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ComicInfoError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("{0}")]
+    Serialize(#[from] quick_xml::SeError),
+    #[error("{0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("{0}")]
+    Archive(#[from] ArchiveError),
+    #[error("ComicInfo.xml can only be written to folders and .cbz/.zip archives: {0}")]
+    ReadOnlyArchive(String),
+    #[error("path is not a folder or archive: {0}")]
+    Path(String),
+}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(error: zip::result::ZipError) -> Self {
+        match error {
+            zip::result::ZipError::UnsupportedArchive(reason) => {
+                ArchiveError::UnsupportedCompression(reason.to_string())
+            }
+            zip::result::ZipError::FileNotFound => {
+                ArchiveError::EntryNotFound(String::from("file not found"))
+            }
+            e => ArchiveError::Zip(e),
+        }
+    }
+}
+
+#[cfg(feature = "cbr")]
+impl From<unrar::error::UnrarError> for ArchiveError {
+    fn from(error: unrar::error::UnrarError) -> Self {
+        match error.code {
+            unrar::error::Code::UnknownFormat => {
+                ArchiveError::UnsupportedCompression(error.to_string())
+            }
+            _ => ArchiveError::Decoder(error.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "cb7")]
+impl From<sevenz_rust::Error> for ArchiveError {
+    fn from(error: sevenz_rust::Error) -> Self {
+        match error {
+            sevenz_rust::Error::UnsupportedCompressionMethod(method) => {
+                ArchiveError::UnsupportedCompression(method)
+            }
+            e => ArchiveError::Decoder(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<pdfium_render::prelude::PdfiumError> for ArchiveError {
+    fn from(error: pdfium_render::prelude::PdfiumError) -> Self {
+        ArchiveError::Decoder(error.to_string())
+    }
+}
+
+impl From<ArchiveError> for ReadDirError {
+    fn from(error: ArchiveError) -> Self {
+        match error {
+            ArchiveError::UnsupportedCompression(method) => {
+                ReadDirError::UnsupportedCompression(method)
+            }
+            e => ReadDirError::Archive(e),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WatcherError {
+    #[error("{0}")]
+    Notify(#[from] notify_debouncer_full::notify::Error),
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PrefetchError {
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+    #[error("{0}")]
+    MangaImage(#[from] MangaImageError),
+    #[error("{0}")]
+    Tuari(#[from] tauri::Error),
+    #[error("panel not found: {0}")]
+    PanelNotFound(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WebtoonError {
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+    #[error("{0}")]
+    MangaImage(#[from] MangaImageError),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Image(#[from] image::ImageError),
+    #[error("{0}")]
+    Tuari(#[from] tauri::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BackupError {
+    #[error("{0:#?}")]
+    NativeDb(#[from] Error),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Tuari(#[from] tauri::Error),
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+    #[error("snapshot not found: {0}")]
+    SnapshotNotFound(String),
+    #[error("{0}")]
+    UnsupportedExport(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -105,6 +277,24 @@ impl From<ReadDirError> for InvokeError {
     }
 }
 
+impl From<PrefetchError> for InvokeError {
+    fn from(error: PrefetchError) -> Self {
+        InvokeError::from_error(error)
+    }
+}
+
+impl From<WebtoonError> for InvokeError {
+    fn from(error: WebtoonError) -> Self {
+        InvokeError::from_error(error)
+    }
+}
+
+impl From<BackupError> for InvokeError {
+    fn from(error: BackupError) -> Self {
+        InvokeError::from_error(error)
+    }
+}
+
 impl From<HttpClientError> for InvokeError {
     fn from(error: HttpClientError) -> Self {
         InvokeError::from_error(error)
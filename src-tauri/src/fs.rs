@@ -1,36 +1,46 @@
-use crate::database::data::v1::User;
+use crate::database::data::v2::User;
 use fast_image_resize::images::Image;
 use fast_image_resize::{IntoImageView, Resizer};
 use futures_util::future::join_all;
 use hashbrown::{HashMap, HashSet};
-use image::codecs::jpeg::JpegEncoder;
-use image::{DynamicImage, ImageEncoder, ImageReader};
+use image::{DynamicImage, ImageReader};
 use rayon::slice::ParallelSliceMut;
-use std::fs::File;
-use std::io::BufWriter;
+use std::io::Cursor;
 use std::iter::Iterator;
 //use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 //use std::time::Instant;
 use futures_util::TryStreamExt;
 //use rayon::iter::IntoParallelRefIterator;
-use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::{env, io};
 use std::{fs::read_dir, process::Command};
 use tokio::io::AsyncWriteExt;
 
-use crate::database::data::v1::MangaPanel;
-use crate::database::delete_panels;
-use crate::database::{data::v1::OsFolder, update_os_folders};
-use crate::database::{delete_os_folders, update_panels, FolderMetadata, HasPath, SortType};
-use crate::misc::get_date_time;
+use crate::archive::{
+    archive_entry_names, entry_panel_paths, is_archive, read_archive_entry,
+    read_archive_panel_paths, split_archive_path, ArchiveKind,
+};
+use crate::comic_info::read_comic_info;
+use crate::covers::{
+    cover_hash, format_cover_img_path, make_thumbnails, thumbnails_exist, CompressedCover,
+};
+use crate::database::data::v2::OsFolder;
+use crate::database::data::v2::{EpubSpine, MangaPanel, SeriesMetadata, ThumbnailSettings};
+use crate::database::{
+    find_library_root, get_thumbnail_settings, save_scanned_entries, set_library_root_offline,
+    sort_panels, FolderMetadata, HasPath, ThumbnailSize,
+};
+use crate::epub::{read_epub_package, EpubPackage};
+use crate::layout::read_dimensions;
+use crate::misc::now_timestamp;
+use crate::scan::{Scan, ScanStage};
+use crate::watcher::watch_library_root;
 use reqwest::Client;
-use tauri::{command, AppHandle, Emitter, Manager};
+use tauri::{command, ipc::Response, AppHandle, Emitter, Manager};
 use tauri_plugin_shell::ShellExt;
 
-use crate::error::{
-    DatabaseError, HttpClientError, MangaImageError, MangaShelfError, ReadDirError,
-};
+use crate::error::{HttpClientError, MangaImageError, MangaShelfError, ReadDirError};
 
 use phf::phf_set;
 
@@ -59,7 +69,15 @@ fn read_dir_helper(
     path: &str,
     child_folder_paths: &mut impl Pushable,
     panel_paths: &mut impl Pushable,
-) -> Result<(), io::Error> {
+) -> Result<(), ReadDirError> {
+    // archives are read like a folder that only contains panels
+    if is_archive(path) && Path::new(path).is_file() {
+        for panel_path in read_archive_panel_paths(path)? {
+            panel_paths.push(panel_path);
+        }
+        return Ok(());
+    }
+
     for entry in read_dir(path)? {
         let entry = entry?;
         let entry_path = entry.path();
@@ -68,6 +86,8 @@ fn read_dir_helper(
             let extension_lossy = extension.to_string_lossy();
             if SUPPORTED_IMAGE_FORMATS.get_key(&extension_lossy).is_some() {
                 panel_paths.push(entry_path.to_string_lossy().to_string());
+            } else if is_archive(&entry_path) && entry_path.is_file() {
+                child_folder_paths.push(entry_path.to_string_lossy().to_string());
             }
         } else if entry_path.is_dir() && read_dir(&entry_path)?.next().is_some() {
             child_folder_paths.push(entry_path.to_string_lossy().to_string());
@@ -78,17 +98,18 @@ fn read_dir_helper(
     Ok(())
 }
 
-type FolderGroup = (OsFolder, Vec<OsFolder>, Vec<MangaPanel>);
-
-fn delete_stale_entries(
-    handle: AppHandle,
-    old_dirs: Vec<OsFolder>,
-    old_panels: Vec<MangaPanel>,
-    user: User,
-) -> Result<(), DatabaseError> {
-    delete_os_folders(handle.clone(), old_dirs, Some(user))?;
-    delete_panels(&handle, old_panels)?;
-    Ok(())
+type FolderGroup = (
+    OsFolder,
+    Vec<OsFolder>,
+    Vec<MangaPanel>,
+    Vec<SeriesMetadata>,
+    Vec<EpubSpine>,
+);
+
+/// a missing root means its drive isn't plugged in.
+/// a root that's there but empty was emptied, so its entries are stale like any other.
+fn is_library_root_available(root: &str) -> bool {
+    Path::new(root).metadata().is_ok()
 }
 
 #[allow(dead_code)]
@@ -198,11 +219,14 @@ fn find_stale_entries(
         None => &mut Vec::new(),
     };
 
+    // panels of the same archive would otherwise list its entries once per panel
+    let mut archive_entries = HashMap::new();
+
     // Filter out missing panels (deleted panels)
     let deleted_panels: Vec<MangaPanel> = old_panels
         .iter()
         .filter_map(|pan| {
-            if !path_exists(&pan.path) {
+            if !path_exists_in(&pan.path, &mut archive_entries) {
                 return Some(pan.clone());
             }
             None
@@ -213,7 +237,7 @@ fn find_stale_entries(
     let deleted_dirs: Vec<OsFolder> = old_dirs
         .iter()
         .filter_map(|dir| {
-            if !path_exists(&dir.path) {
+            if !path_exists_in(&dir.path, &mut archive_entries) {
                 return Some(dir.clone());
             }
             None
@@ -253,6 +277,19 @@ pub async fn upsert_read_os_dir(
     mut old_panels: Option<Vec<MangaPanel>>,
 ) -> Result<bool, MangaShelfError> {
     let id = user.id.clone();
+    let is_root = parent_path.is_none();
+
+    // an unplugged drive looks exactly like a deleted library, so its entries
+    // and read progress are kept until the root is available again
+    if let Some(root) = find_library_root(&handle, &dir)? {
+        if !is_library_root_available(&root) {
+            set_library_root_offline(&handle, &root, &id, true)?;
+            return Ok(false);
+        }
+        set_library_root_offline(&handle, &root, &id, false)?;
+        watch_library_root(&handle, &root);
+    }
+
     // Find stale entries based on the provided directory and old data.
     let mut stale_entries = find_stale_entries(&dir, old_dirs.as_mut(), old_panels.as_mut())?;
     //println!("stale_entries: {:#?}", stale_entries);
@@ -263,14 +300,15 @@ pub async fn upsert_read_os_dir(
         return Ok(false);
     }
 
+    // the deleted entries are only removed with the scan's final write, so a cancelled scan keeps them
+    let mut stale_deletions = None;
     if let StaleEntries::Found {
         ref mut deleted, ..
     } = stale_entries
     {
-        if let Some(deleted_entries) = deleted.take() {
-            // Only move the deleted entries.
-            delete_stale_entries(handle.clone(), deleted_entries.0, deleted_entries.1, user)?;
-        }
+        stale_deletions = deleted
+            .take()
+            .map(|(old_dirs, old_panels)| (old_dirs, old_panels, user));
     }
 
     if let StaleEntries::Found { dirs, panels, .. } = &stale_entries {
@@ -279,39 +317,80 @@ pub async fn upsert_read_os_dir(
         }
     }
 
-    let (main_folder, mut new_cfs, panels) =
-        read_os_folder_dir(dir, id, None, parent_path, stale_entries)?;
+    let dir_path = dir.clone();
+    let thumbnail_settings = get_thumbnail_settings(handle.clone(), id.clone())?;
+    let scan = Scan::start(&handle, &dir);
+    let (main_folder, mut new_cfs, panels, series_metadata, epub_spines) =
+        match read_os_folder_dir(dir, id, None, parent_path, stale_entries, &scan) {
+            Ok(group) => group,
+            Err(e) => {
+                if scan.is_cancelled() {
+                    scan.emit(ScanStage::Cancelled);
+                }
+                return Err(e.into());
+            }
+        };
+
+    // only the headers are read, so this is cheap next to compressing the covers
+    let panel_dimensions = read_dimensions(&panels, Some(&scan));
 
     let app_data_dir = handle.path().app_data_dir()?;
 
     new_cfs.push(main_folder);
-    let instant = std::time::Instant::now();
-    let compressed_imgs: Vec<EncoderWriterPair> = new_cfs
-        .par_iter_mut()
-        .filter_map(|cf: &mut OsFolder| {
-            if let Some(ref input) = cf.cover_img_path {
-                let app_data_cover_img_path = format_cover_img_path(
-                    input,
-                    &app_data_dir,
-                    (cf.parent_path.as_deref(), cf.path.as_ref()),
-                )
-                .ok();
-
-                if let Some(Some(output)) = app_data_cover_img_path {
-                    if let Ok(pair) = compress_cover_panel(input, &output) {
-                        cf.cover_img_path = Some(output);
-                        return Some(pair);
-                    }
+    scan.emit(ScanStage::CompressingCovers);
+
+    // parent folders use the cover of their first child, so every panel is only compressed once
+    let cover_inputs: HashSet<String> = new_cfs
+        .iter()
+        .filter_map(|cf| cf.cover_img_path.clone())
+        .collect();
+    let compressed_covers: Vec<(String, CompressedCover)> = cover_inputs
+        .into_par_iter()
+        .filter_map(|input| {
+            if scan.is_cancelled() {
+                return None;
+            }
+            match compress_cover_panel(&input, &app_data_dir, &thumbnail_settings) {
+                Ok(cover) => {
+                    scan.cover_compressed(&input);
+                    Some((input, cover))
+                }
+                Err(e) => {
+                    eprintln!("failed to compress cover {input}: {e}");
+                    None
                 }
             }
-            None
         })
         .collect();
 
+    // nothing has been written to the database yet, so stopping here leaves it as it was
+    if scan.is_cancelled() {
+        scan.emit(ScanStage::Cancelled);
+        return Err(ReadDirError::ScanCancelled(dir_path).into());
+    }
+
+    let mut cover_outputs: HashMap<String, String> = HashMap::new();
+    let mut cover_variants = Vec::new();
+    let mut pending_thumbnails = Vec::new();
+    for (input, cover) in compressed_covers {
+        cover_outputs.insert(input, cover.path);
+        cover_variants.extend(cover.variants);
+        pending_thumbnails.extend(cover.pending);
+    }
+    for cf in new_cfs.iter_mut() {
+        if let Some(output) = cf
+            .cover_img_path
+            .as_ref()
+            .and_then(|i| cover_outputs.get(i))
+        {
+            cf.cover_img_path = Some(output.clone());
+        }
+    }
+
     let mut first_task = None;
-    for (i, (dimg, encoder)) in compressed_imgs.into_iter().enumerate() {
+    for (i, thumbnail) in pending_thumbnails.into_iter().enumerate() {
         let task = tokio::spawn(async move {
-            if let Err(e) = dimg.write_with_encoder(encoder) {
+            if let Err(e) = thumbnail.write() {
                 eprintln!("{}", e);
             }
         });
@@ -324,14 +403,22 @@ pub async fn upsert_read_os_dir(
         first.await.ok();
     }
 
-    println!(
-        "finished compressing {} folders in {}ms",
-        new_cfs.len(),
-        instant.elapsed().as_millis()
-    );
-
-    update_panels(&handle, panels, None)?;
-    update_os_folders(handle, new_cfs, None)?;
+    scan.emit(ScanStage::Saving);
+    save_scanned_entries(
+        &handle,
+        stale_deletions,
+        new_cfs,
+        panels,
+        series_metadata,
+        epub_spines,
+        cover_variants,
+        panel_dimensions,
+    )?;
+    scan.emit(ScanStage::Finished);
+
+    if is_root {
+        watch_library_root(&handle, &dir_path);
+    }
 
     // Indicate whether a refetch was performed.
     Ok(true)
@@ -340,13 +427,28 @@ pub async fn upsert_read_os_dir(
 pub fn read_os_folder_dir(
     path: String,
     user_id: String,
-    update_datetime: Option<(String, String)>,
+    updated_at: Option<i64>,
     parent_path: Option<String>,
     stale_entries: StaleEntries,
+    scan: &Scan,
 ) -> Result<FolderGroup, ReadDirError> {
+    scan.check_cancelled()?;
+
     let mut childfolder_paths = HashSet::new();
     let mut panel_paths = HashSet::new();
-    read_dir_helper(&path, &mut childfolder_paths, &mut panel_paths)?;
+
+    // an epub's opf is only parsed once, for its pages, title and spine order
+    let is_epub =
+        ArchiveKind::from_path(&path) == Some(ArchiveKind::Epub) && Path::new(&path).is_file();
+    let epub = is_epub.then(|| read_epub_package(&path)).transpose()?;
+    let epub_spine = epub.as_ref().map(|pkg| EpubSpine {
+        folder_path: path.clone(),
+        pages: entry_panel_paths(Path::new(&path), &pkg.pages),
+    });
+    match &epub_spine {
+        Some(spine) => panel_paths.extend(spine.pages.iter().cloned()),
+        None => read_dir_helper(&path, &mut childfolder_paths, &mut panel_paths)?,
+    }
 
     let parent_path = parent_path.is_some().then(|| {
         Path::new(&path)
@@ -376,43 +478,52 @@ pub fn read_os_folder_dir(
     }
     let os_folder_path_clone = path.clone();
     let os_folder = Path::new(&os_folder_path_clone);
-    let (update_date, update_time) = update_datetime.clone().unwrap_or_else(get_date_time);
+    let updated_at = updated_at.unwrap_or_else(now_timestamp);
 
     let mut total_child_folders: Vec<OsFolder> = Vec::new();
     let mut total_panels: Vec<MangaPanel> = Vec::new();
     let current_folders_panels: Vec<MangaPanel> = panel_paths
         .into_par_iter()
         .filter_map(|panel_path| {
-            MangaPanel::new(
-                user_id.clone(),
-                path.clone(),
-                panel_path,
-                update_date.clone(),
-                update_time.clone(),
-            )
-            .ok()
+            MangaPanel::new(user_id.clone(), path.clone(), panel_path, updated_at).ok()
         })
         .collect::<Vec<MangaPanel>>();
     let is_manga_folder = !current_folders_panels.is_empty();
+    scan.folder_read(&path, current_folders_panels.len());
     total_panels.extend(current_folders_panels);
 
-    total_panels.par_sort_by(SortType::sort(&SortType::EpisodeTitleRegex));
+    sort_panels(epub_spine.as_ref(), &mut total_panels);
 
     let first_panel = total_panels.first().cloned();
     //println!("first_panel: {:?}", first_panel); // Debug statement
     let mut cover_img = first_panel.as_ref().map(|p| p.path.clone());
 
+    let mut total_series_metadata: Vec<SeriesMetadata> = Vec::new();
+    let mut total_epub_spines: Vec<EpubSpine> = epub_spine.into_iter().collect();
+    match read_comic_info(&path) {
+        Ok(Some(comic_info)) => total_series_metadata
+            .push(comic_info.into_series_metadata(user_id.clone(), path.clone())),
+        Ok(None) => {}
+        Err(e) => eprintln!("failed to read ComicInfo.xml in {path}: {e}"),
+    }
+
     let child_folders_group: Vec<FolderGroup> = childfolder_paths
         .into_par_iter()
         .filter_map(|folder_path| {
             match read_os_folder_dir(
-                folder_path,
+                folder_path.clone(),
                 user_id.clone(),
-                update_datetime.clone(),
+                Some(updated_at),
                 Some(path.clone()),
                 StaleEntries::None,
+                scan,
             ) {
                 Ok(i) => Some(i),
+                // the archive is left out, but the user should know why it's missing
+                Err(e @ ReadDirError::UnsupportedCompression(_)) => {
+                    scan.folder_skipped(&folder_path, &e);
+                    None
+                }
                 Err(e) => {
                     eprintln!("{}", e);
                     None
@@ -421,8 +532,11 @@ pub fn read_os_folder_dir(
         })
         .collect();
 
+    // cancelled children are skipped above, so make sure the cancel reaches the top
+    scan.check_cancelled()?;
+
     for group in child_folders_group.into_iter() {
-        let (folder, c_folders, g_panels) = group;
+        let (folder, c_folders, g_panels, g_series_metadata, g_epub_spines) = group;
 
         if cover_img.is_none() {
             if let Some(cover_img_path) = &folder.cover_img_path {
@@ -433,13 +547,15 @@ pub fn read_os_folder_dir(
         total_panels.extend(g_panels);
         total_child_folders.push(folder);
         total_child_folders.extend(c_folders);
+        total_series_metadata.extend(g_series_metadata);
+        total_epub_spines.extend(g_epub_spines);
     }
     let metadata = FolderMetadata::from_path(&path, total_panels.len(), total_child_folders.len());
 
     let main_folder = OsFolder {
         user_id,
+        title: folder_title(os_folder, epub.as_ref()),
         path,
-        title: os_folder.file_name().unwrap().to_string_lossy().to_string(),
         parent_path,
         last_read_panel: first_panel,
         cover_img_path: cover_img,
@@ -449,83 +565,77 @@ pub fn read_os_folder_dir(
         is_read: false,
         zoom: 100,
         is_hidden: false,
-        update_date,
-        update_time,
+        updated_at,
     };
 
-    Ok((main_folder, total_child_folders, total_panels))
+    Ok((
+        main_folder,
+        total_child_folders,
+        total_panels,
+        total_series_metadata,
+        total_epub_spines,
+    ))
 }
 
-fn format_cover_img_path(
-    img_path: &str,
-    app_data_dir: &Path,
-    parent_paths: (Option<&str>, &str),
-) -> Result<Option<String>, ReadDirError> {
-    let file_title = Path::new(&img_path).file_stem().ok_or_else(|| {
-        eprintln!("Error: Invalid file title for cover_img: {}", img_path);
-        ReadDirError::Path(img_path.to_string())
-    })?;
-
-    #[allow(unused_assignments)]
-    let mut final_path = None;
-    let (super_parent, parent) = parent_paths;
-    let parent_dir_title = Path::new(&parent).file_name().ok_or_else(|| {
-        eprintln!("Error: Invalid parent_dir_title for path: {}", parent);
-        ReadDirError::Path(parent.to_string())
-    })?;
-    if let Some(super_parent) = super_parent {
-        let super_parent_title = Path::new(&super_parent).file_name().ok_or_else(|| {
-            eprintln!(
-                "Error: Invalid super_parent_title for parent: {}",
-                super_parent
-            );
-            ReadDirError::Path(super_parent.to_string())
-        })?;
-
-        let cover_path = app_data_dir
-            .join("covers")
-            .join(super_parent_title)
-            .join(parent_dir_title)
-            .join(file_title)
-            .with_extension("cmp.jpg")
-            .to_string_lossy()
-            .to_string();
-        final_path = Some(cover_path);
-    } else {
-        let cover_path = app_data_dir
-            .join("covers")
-            .join(parent_dir_title)
-            .join(file_title)
-            .with_extension("cmp.jpg")
-            .to_string_lossy()
-            .to_string();
-        final_path = Some(cover_path);
+/// epubs use their `dc:title`, other archives drop their extension
+fn folder_title(path: &Path, epub: Option<&EpubPackage>) -> String {
+    if let Some(title) = epub.and_then(|pkg| pkg.title.clone()) {
+        return title;
     }
-    Ok(final_path)
-}
 
-pub type EncoderWriterPair = (DynamicImage, JpegEncoder<BufWriter<File>>);
+    let title = if is_archive(path) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+    title.unwrap().to_string_lossy().to_string()
+}
 
+/// compresses a panel into the cover cache, along with every thumbnail size in the settings.
+/// nothing has to be written if the same panel was already compressed with the same settings.
 pub fn compress_cover_panel(
-    input: impl AsRef<Path>,
-    output: impl AsRef<Path>,
-) -> Result<EncoderWriterPair, MangaImageError> {
-    let img_path = input.as_ref();
-    let output = output.as_ref();
-    let img = image::open(img_path)?;
-    let max_size = 1200;
-
-    let resized = img.thumbnail(max_size, max_size);
-    if let Some(parent) = output.parent() {
-        std::fs::create_dir_all(parent).ok();
+    input: &str,
+    app_data_dir: &Path,
+    settings: &ThumbnailSettings,
+) -> Result<CompressedCover, MangaImageError> {
+    let bytes = read_panel_bytes(input)?;
+    let hash = cover_hash(input, &bytes, settings);
+    if thumbnails_exist(&hash, app_data_dir, settings) {
+        let path = format_cover_img_path(app_data_dir, &hash, ThumbnailSize::Full, settings.format);
+        return Ok(CompressedCover {
+            path: path.to_string_lossy().to_string(),
+            variants: None,
+            pending: Vec::new(),
+        });
     }
 
-    let output_file = File::create(output).unwrap();
-    let buf = BufWriter::new(output_file);
-    let encoder = JpegEncoder::new_with_quality(buf, 95);
-    //resized.write_with_encoder(encoder)?;
+    let img = decode_panel_image(bytes)?;
+    Ok(make_thumbnails(img, &hash, app_data_dir, settings))
+}
 
-    Ok((resized, encoder))
+/// reads a panel from disk, or from the archive it lives in
+pub fn read_panel_bytes(path: impl AsRef<Path>) -> Result<Vec<u8>, MangaImageError> {
+    let path = path.as_ref();
+    if let Some((archive, entry)) = split_archive_path(path) {
+        return Ok(read_archive_entry(archive, &entry)?);
+    }
+
+    Ok(std::fs::read(path)?)
+}
+
+pub fn decode_panel_image(bytes: Vec<u8>) -> Result<DynamicImage, MangaImageError> {
+    let img = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()?;
+    Ok(img)
+}
+
+/// streams the raw bytes of a panel that lives inside of an archive
+#[command]
+pub async fn read_archive_panel(path: String) -> Result<Response, ReadDirError> {
+    let (archive, entry) = split_archive_path(&path).ok_or(ReadDirError::Path(path.clone()))?;
+    let bytes = read_archive_entry(archive, &entry)?;
+    Ok(Response::new(bytes))
 }
 
 #[command]
@@ -603,9 +713,28 @@ pub async fn download_mpv_binary(handle: AppHandle) -> Result<String, HttpClient
     Ok(mpv_file_path.to_string_lossy().to_string())
 }
 
+/// a path inside of an archive exists if the archive has an entry for it, or entries inside of it
 #[tauri::command]
 pub fn path_exists(path: &str) -> bool {
-    Path::exists(&PathBuf::from(path))
+    path_exists_in(path, &mut HashMap::new())
+}
+
+/// `path_exists` that lists each archive only once, for checking many paths of the same archives
+fn path_exists_in(path: &str, archive_entries: &mut HashMap<PathBuf, Option<Vec<String>>>) -> bool {
+    if Path::exists(&PathBuf::from(path)) {
+        return true;
+    }
+    let Some((archive, entry)) = split_archive_path(path) else {
+        return false;
+    };
+
+    let names = archive_entries
+        .entry(archive)
+        .or_insert_with_key(|archive| archive_entry_names(archive).ok());
+    let dir = format!("{}/", entry.trim_end_matches('/'));
+    names
+        .as_ref()
+        .is_some_and(|names| names.iter().any(|n| *n == entry || n.starts_with(&dir)))
 }
 
 #[tauri::command]
@@ -0,0 +1,313 @@
+use std::path::Path;
+#[cfg(feature = "pdf")]
+use std::path::PathBuf;
+
+use hashbrown::{HashMap, HashSet};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+#[cfg(feature = "pdf")]
+use crate::{archive::ArchiveKind, pdf};
+use crate::{
+    archive::{read_archive_entry_head, split_archive_path},
+    database::{
+        data::v2::{MangaPanel, PanelDimensions},
+        get_panel_dimensions, get_panels, get_series_metadata, save_panel_dimensions,
+    },
+    error::DatabaseError,
+    profile::resolve_reading_profile,
+    scan::Scan,
+    split::reader_panels,
+};
+
+/// enough of an archive entry for `imagesize` to find the frame header in,
+/// jpegs can have a big exif block in front of it
+const HEADER_BYTES: u64 = 256 * 1024;
+
+/// panels wider than they are tall are scans of two pages
+pub const SPREAD_ASPECT_RATIO: f32 = 1.0;
+
+/// reads the size of a panel from its image header, without decoding it
+pub fn read_panel_dimensions(panel: &MangaPanel) -> Option<PanelDimensions> {
+    let (width, height) = match split_archive_path(&panel.path) {
+        // a pdf is measured a whole document at a time by `read_pdf_dimensions`
+        #[cfg(feature = "pdf")]
+        Some((archive, _)) if ArchiveKind::from_path(&archive) == Some(ArchiveKind::Pdf) => {
+            return None
+        }
+        Some((archive, entry)) => {
+            let head = read_archive_entry_head(&archive, &entry, HEADER_BYTES).ok()?;
+            let size = imagesize::blob_size(&head).ok()?;
+            (size.width as u32, size.height as u32)
+        }
+        None => {
+            let size = imagesize::size(Path::new(&panel.path)).ok()?;
+            (size.width as u32, size.height as u32)
+        }
+    };
+
+    Some(PanelDimensions::new(
+        panel.path.clone(),
+        panel.parent_path.clone(),
+        width,
+        height,
+    ))
+}
+
+/// measures every page of the pdfs the panels are in, opening each pdf once
+#[cfg(feature = "pdf")]
+fn read_pdf_dimensions(panels: &[MangaPanel], scan: Option<&Scan>) -> Vec<PanelDimensions> {
+    let mut pdfs: HashMap<PathBuf, Vec<(&MangaPanel, u16)>> = HashMap::new();
+    for panel in panels {
+        let Some((archive, entry)) = split_archive_path(&panel.path) else {
+            continue;
+        };
+        if ArchiveKind::from_path(&archive) == Some(ArchiveKind::Pdf) {
+            if let Some(index) = pdf::page_index(&entry) {
+                pdfs.entry(archive).or_default().push((panel, index));
+            }
+        }
+    }
+
+    pdfs.par_iter()
+        .filter_map(|(pdf, pages)| {
+            if scan.is_some_and(|s| s.is_cancelled()) {
+                return None;
+            }
+            let sizes = pdf::page_sizes(pdf).ok()?;
+            Some(
+                pages
+                    .iter()
+                    .filter_map(|(panel, index)| {
+                        let (width, height) = sizes.get(*index as usize)?;
+                        Some(PanelDimensions::new(
+                            panel.path.clone(),
+                            panel.parent_path.clone(),
+                            *width,
+                            *height,
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// measures the panels on the rayon pool, skipping the ones that can't be read
+pub fn read_dimensions(panels: &[MangaPanel], scan: Option<&Scan>) -> Vec<PanelDimensions> {
+    let measured = panels.par_iter().filter_map(|panel| {
+        if scan.is_some_and(|s| s.is_cancelled()) {
+            return None;
+        }
+        read_panel_dimensions(panel)
+    });
+
+    #[cfg(feature = "pdf")]
+    let measured = measured.chain(read_pdf_dimensions(panels, scan));
+
+    measured.collect()
+}
+
+/// one screen of the reader. `first` is the page on the right for right-to-left reading.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Spread {
+    /// indexes into the folder's sorted panels
+    pub first: usize,
+    pub second: Option<usize>,
+    /// a single panel that already holds two pages
+    pub is_wide: bool,
+}
+
+/// pairs up the panels, giving wide panels a spread of their own.
+/// `offset_cover` shows the first panel alone, so the pages after it line up the way they were printed.
+pub fn compute_spreads(wide: &[bool], offset_cover: bool) -> Vec<Spread> {
+    let mut spreads = Vec::with_capacity(wide.len() / 2 + 1);
+    let mut i = 0;
+
+    if offset_cover && !wide.is_empty() {
+        spreads.push(Spread {
+            first: 0,
+            second: None,
+            is_wide: wide[0],
+        });
+        i = 1;
+    }
+
+    while i < wide.len() {
+        if wide[i] {
+            spreads.push(Spread {
+                first: i,
+                second: None,
+                is_wide: true,
+            });
+            i += 1;
+        } else if i + 1 < wide.len() && !wide[i + 1] {
+            spreads.push(Spread {
+                first: i,
+                second: Some(i + 1),
+                is_wide: false,
+            });
+            i += 2;
+        } else {
+            // the page before a wide panel, or the last page, is shown alone
+            spreads.push(Spread {
+                first: i,
+                second: None,
+                is_wide: false,
+            });
+            i += 1;
+        }
+    }
+
+    spreads
+}
+
+/// the dimensions of the panels by their path.
+/// panels scanned before their dimensions were stored are measured here and saved.
+pub fn measure_panels(
+    handle: &AppHandle,
+    parent_path: &str,
+    panels: &[MangaPanel],
+) -> Result<HashMap<String, PanelDimensions>, DatabaseError> {
+    let mut dimensions: HashMap<String, PanelDimensions> =
+        get_panel_dimensions(handle, parent_path)?
+            .into_iter()
+            .map(|d| (d.path.clone(), d))
+            .collect();
+
+    let unmeasured: Vec<MangaPanel> = panels
+        .iter()
+        .filter(|p| !dimensions.contains_key(&p.path))
+        .cloned()
+        .collect();
+    if !unmeasured.is_empty() {
+        let measured = read_dimensions(&unmeasured, None);
+        dimensions.extend(measured.iter().map(|d| (d.path.clone(), d.clone())));
+        save_panel_dimensions(handle, measured)?;
+    }
+
+    Ok(dimensions)
+}
+
+/// works out how the folder's panels are paired up in double panel mode.
+/// `offset_cover` defaults to the folder's reading profile.
+#[command]
+pub async fn get_spread_layout(
+    handle: AppHandle,
+    parent_path: String,
+    offset_cover: Option<bool>,
+) -> Result<Vec<Spread>, DatabaseError> {
+    tauri::async_runtime::spawn_blocking(move || spread_layout(&handle, parent_path, offset_cover))
+        .await?
+}
+
+fn spread_layout(
+    handle: &AppHandle,
+    parent_path: String,
+    offset_cover: Option<bool>,
+) -> Result<Vec<Spread>, DatabaseError> {
+    let profile = resolve_reading_profile(handle, &parent_path)?;
+    let panels = reader_panels(handle, &parent_path)?;
+    let dimensions = measure_panels(handle, &parent_path, &panels)?;
+
+    // a ComicInfo.xml can flag double pages by their index in the folder,
+    // which doesn't count the halves of split panels
+    let mut double_pages = HashSet::new();
+    if let Ok(metadata) = get_series_metadata(handle.clone(), parent_path.clone()) {
+        let source_panels = get_panels(handle.clone(), parent_path)?;
+        double_pages.extend(
+            metadata
+                .pages
+                .iter()
+                .filter(|p| p.is_double_page)
+                .filter_map(|p| source_panels.get(p.image as usize))
+                .map(|p| p.path.clone()),
+        );
+    }
+
+    let wide: Vec<bool> = panels
+        .iter()
+        .map(|p| {
+            double_pages.contains(&p.path)
+                || dimensions
+                    .get(&p.path)
+                    .is_some_and(|d| d.aspect_ratio > SPREAD_ASPECT_RATIO)
+        })
+        .collect();
+
+    Ok(compute_spreads(
+        &wide,
+        offset_cover.unwrap_or(profile.offset_cover),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(first: usize, is_wide: bool) -> Spread {
+        Spread {
+            first,
+            second: None,
+            is_wide,
+        }
+    }
+
+    fn pair(first: usize) -> Spread {
+        Spread {
+            first,
+            second: Some(first + 1),
+            is_wide: false,
+        }
+    }
+
+    #[test]
+    fn pairs_pages() {
+        assert_eq!(compute_spreads(&[], false), vec![]);
+        assert_eq!(compute_spreads(&[], true), vec![]);
+        assert_eq!(compute_spreads(&[false; 4], false), vec![pair(0), pair(2)]);
+        // the last page is left alone
+        assert_eq!(
+            compute_spreads(&[false; 5], false),
+            vec![pair(0), pair(2), single(4, false)]
+        );
+    }
+
+    #[test]
+    fn offsets_the_cover() {
+        assert_eq!(
+            compute_spreads(&[false; 5], true),
+            vec![single(0, false), pair(1), pair(3)]
+        );
+        // a wide cover is still shown alone
+        assert_eq!(
+            compute_spreads(&[true, false, false], true),
+            vec![single(0, true), pair(1)]
+        );
+    }
+
+    #[test]
+    fn gives_wide_panels_their_own_spread() {
+        assert_eq!(
+            compute_spreads(&[false, false, true, false, false], false),
+            vec![pair(0), single(2, true), pair(3)]
+        );
+        // the page before a wide panel is shown alone instead of being paired across it
+        assert_eq!(
+            compute_spreads(&[false, true, false, true, true], false),
+            vec![
+                single(0, false),
+                single(1, true),
+                single(2, false),
+                single(3, true),
+                single(4, true)
+            ]
+        );
+        assert_eq!(
+            compute_spreads(&[false, false, false, true], true),
+            vec![single(0, false), pair(1), single(3, true)]
+        );
+    }
+}
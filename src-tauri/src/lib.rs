@@ -1,24 +1,85 @@
 #![feature(iterator_try_collect)]
 
+use std::path::PathBuf;
+
 use database::init_database;
-use tauri::Manager;
+use error::InitError;
+use tauri::{AppHandle, Manager, WebviewWindowBuilder};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use tray::kill_dup_process;
 
+mod archive;
+mod backup;
+mod comic_info;
+mod covers;
+mod crop;
 mod database;
+mod epub;
 mod error;
 mod fs;
+mod layout;
 mod misc;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod prefetch;
+mod profile;
+mod protocol;
+mod repository;
+mod scan;
+mod sort;
+mod split;
 mod tray;
+mod watcher;
+mod webtoon;
 
+use crate::backup::{
+    create_database_snapshot, export_library, get_database_snapshots, import_library,
+    init_snapshot_schedule, restore_database_snapshot,
+};
+use crate::comic_info::save_series_metadata;
+use crate::covers::collect_cover_garbage;
+use crate::crop::{get_auto_crop, get_folder_auto_crop, set_folder_auto_crop};
 use crate::database::{
-    delete_os_folders, get_default_user, get_next_folder, get_os_folder_by_path, get_os_folders,
-    get_os_folders_by_path, get_panels, get_prev_folder, get_user_by_id, update_os_folders,
-    update_user,
+    delete_os_folders, get_cover_variants, get_default_user, get_library_roots, get_next_folder,
+    get_os_folder_by_path, get_os_folders, get_os_folders_by_path, get_panels, get_prev_folder,
+    get_series_metadata, get_thumbnail_settings, get_user_by_id, relocate_library,
+    search_series_metadata, update_os_folders, update_thumbnail_settings, update_user,
 };
 use crate::fs::{
-    check_cover_img_exists, download_mpv_binary, path_exists, show_in_folder, upsert_read_os_dir,
+    check_cover_img_exists, download_mpv_binary, path_exists, read_archive_panel, show_in_folder,
+    upsert_read_os_dir,
 };
+use crate::layout::get_spread_layout;
+#[cfg(feature = "pdf")]
+use crate::pdf::get_pdf_page;
+use crate::prefetch::{cancel_prefetch, get_cached_panel, prefetch_panels, PanelCache};
+use crate::profile::{
+    get_folder_reading_profile_overrides, get_reading_profile, set_folder_reading_profile,
+};
+use crate::protocol::{handle_shelf_request, PageCache, SHELF_SCHEME};
+use crate::scan::{cancel_scan, ScanRegistry};
+use crate::split::{get_folder_split_spreads, get_reader_panels, set_folder_split_spreads};
 use crate::tray::init_tray;
+use crate::watcher::init_watcher;
+use crate::webtoon::get_webtoon_tiles;
+
+fn open_library(handle: &AppHandle) -> Result<PathBuf, InitError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    init_database(&app_data_dir, handle)?;
+    Ok(app_data_dir)
+}
+
+/// release builds have no console, so the error gets a dialog and the app exits once it's closed
+fn show_init_error(handle: &AppHandle, error: InitError) {
+    eprintln!("failed to start: {error}");
+    let exit_handle = handle.clone();
+    handle
+        .dialog()
+        .message(error.to_string())
+        .title("mangashelf couldn't open its library")
+        .kind(MessageDialogKind::Error)
+        .show(move |_| exit_handle.exit(1));
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -27,12 +88,39 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
+        .register_asynchronous_uri_scheme_protocol(SHELF_SCHEME, handle_shelf_request)
         .setup(move |app| {
             let handle = app.handle();
-            let app_data_dir = handle.path().app_data_dir().unwrap();
-            init_database(&app_data_dir, handle).unwrap();
+            let app_data_dir = match open_library(handle) {
+                Ok(app_data_dir) => app_data_dir,
+                Err(e) => {
+                    show_init_error(handle, e);
+                    return Ok(());
+                }
+            };
+            init_snapshot_schedule(handle);
+            handle.manage(ScanRegistry::default());
+            match PageCache::load(&app_data_dir) {
+                Ok(page_cache) => {
+                    handle.manage(page_cache);
+                }
+                Err(e) => eprintln!("failed to load the page cache: {e}"),
+            }
+            match PanelCache::new() {
+                Ok(panel_cache) => {
+                    handle.manage(panel_cache);
+                }
+                Err(e) => eprintln!("failed to start the panel prefetcher: {e}"),
+            }
+            if let Err(e) = init_watcher(handle) {
+                eprintln!("failed to start the library watcher: {e}");
+            }
             kill_dup_process();
             init_tray(app).unwrap();
+            // the window is only created once the database is open, every command needs it
+            for config in &app.config().app.windows {
+                WebviewWindowBuilder::from_config(handle, config)?.build()?;
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -52,6 +140,38 @@ pub fn run() {
             download_mpv_binary,
             path_exists,
             upsert_read_os_dir,
+            read_archive_panel,
+            #[cfg(feature = "pdf")]
+            get_pdf_page,
+            get_series_metadata,
+            search_series_metadata,
+            save_series_metadata,
+            cancel_scan,
+            get_library_roots,
+            relocate_library,
+            collect_cover_garbage,
+            get_thumbnail_settings,
+            update_thumbnail_settings,
+            get_cover_variants,
+            prefetch_panels,
+            get_cached_panel,
+            cancel_prefetch,
+            get_spread_layout,
+            get_reader_panels,
+            get_folder_split_spreads,
+            set_folder_split_spreads,
+            get_auto_crop,
+            get_folder_auto_crop,
+            set_folder_auto_crop,
+            get_reading_profile,
+            get_folder_reading_profile_overrides,
+            set_folder_reading_profile,
+            get_webtoon_tiles,
+            create_database_snapshot,
+            get_database_snapshots,
+            restore_database_snapshot,
+            export_library,
+            import_library,
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application")
@@ -1,30 +1,82 @@
-use chrono::Local;
-use std::mem::take;
-
-pub fn get_date_time() -> (String, String) {
-    let local_t = Local::now();
-    let date_t_string = local_t.format("%Y-%m-%d %H:%M").to_string();
-
-    let mut date_vec: Vec<String> = date_t_string
-        .split_whitespace()
-        .map(|x| x.to_owned())
-        .collect();
-
-    if date_vec.len() == 2 {
-        let mut time = take(&mut date_vec[1]);
-        if let Some((h, m)) = time.split_once(":") {
-            if let Ok(int_h) = h.parse::<u8>() {
-                time = format!(
-                    "{:02}:{:02}{}",
-                    int_h % 12,
-                    m,
-                    if int_h >= 12 { "pm" } else { "am" }
-                );
-            }
-        }
-
-        return (take(&mut date_vec[0]), time);
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+
+/// milliseconds since the unix epoch in UTC, what every `updated_at` is stored as
+pub fn now_timestamp() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// reads the `update_date` and `update_time` of the v1 models, like "2024-11-30" and "10:43pm",
+/// in the local time they were written in.
+/// v1 wrote the hour `% 12`, so noon was stored as "00:43pm".
+pub fn parse_legacy_date_time(date: &str, time: &str) -> Option<i64> {
+    let (clock, suffix) = time
+        .trim()
+        .split_at_checked(time.trim().len().checked_sub(2)?)?;
+    let naive_time = NaiveTime::parse_from_str(clock, "%H:%M").ok()?;
+    let hour = match suffix.to_ascii_lowercase().as_str() {
+        "am" => naive_time.hour() % 12,
+        "pm" => naive_time.hour() % 12 + 12,
+        _ => return None,
+    };
+
+    let naive = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(hour, naive_time.minute(), 0)?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|t| t.timestamp_millis())
+}
+
+/// the `update_date` and `update_time` of the v1 models, for a v2 model that's read as v1
+pub fn format_legacy_date_time(timestamp: i64) -> (String, String) {
+    match Local.timestamp_millis_opt(timestamp).single() {
+        Some(t) => (
+            t.format("%Y-%m-%d").to_string(),
+            t.format("%I:%M%P").to_string(),
+        ),
+        None => (String::new(), String::new()),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_noon_is_after_the_morning() {
+        let morning = parse_legacy_date_time("2024-11-30", "11:59am").unwrap();
+        let noon = parse_legacy_date_time("2024-11-30", "00:15pm").unwrap();
+        let evening = parse_legacy_date_time("2024-11-30", "10:43pm").unwrap();
+        let midnight = parse_legacy_date_time("2024-11-30", "00:05am").unwrap();
 
-    (String::new(), String::new())
+        assert!(midnight < morning);
+        assert!(morning < noon);
+        assert!(noon < evening);
+    }
+
+    #[test]
+    fn legacy_twelve_and_zero_are_the_same_hour() {
+        assert_eq!(
+            parse_legacy_date_time("2024-11-30", "12:15pm"),
+            parse_legacy_date_time("2024-11-30", "00:15pm"),
+        );
+    }
+
+    #[test]
+    fn legacy_strings_round_trip() {
+        let timestamp = parse_legacy_date_time("2024-11-30", "03:07pm").unwrap();
+        let (date, time) = format_legacy_date_time(timestamp);
+
+        assert_eq!(date, "2024-11-30");
+        assert_eq!(time, "03:07pm");
+        assert_eq!(parse_legacy_date_time(&date, &time), Some(timestamp));
+    }
+
+    #[test]
+    fn invalid_legacy_strings() {
+        assert_eq!(parse_legacy_date_time("", ""), None);
+        assert_eq!(parse_legacy_date_time("2024-11-30", "10:43"), None);
+        assert_eq!(parse_legacy_date_time("30/11/2024", "10:43pm"), None);
+    }
 }
@@ -0,0 +1,169 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use image::{codecs::jpeg::JpegEncoder, DynamicImage};
+use tauri::{command, AppHandle, Manager};
+
+use crate::{
+    archive::split_archive_path,
+    error::{ArchiveError, ReadDirError},
+};
+
+const PAGE_JPEG_QUALITY: u8 = 90;
+
+/// pdf pages are listed as `0001.jpg`, `0002.jpg`, ... inside of the pdf's virtual folder
+pub fn page_entry_name(index: u16) -> String {
+    format!("{:04}.jpg", index as u32 + 1)
+}
+
+pub fn page_index(entry: &str) -> Option<u16> {
+    Path::new(entry)
+        .file_stem()?
+        .to_str()?
+        .parse::<u16>()
+        .ok()?
+        .checked_sub(1)
+}
+
+/// lists every page of the pdf as an entry name
+pub fn page_entry_names(pdf: &Path) -> Result<Vec<String>, ArchiveError> {
+    let count = render::page_count(pdf)?;
+    Ok((0..count).map(page_entry_name).collect())
+}
+
+/// renders a page into jpeg bytes, so pdfs can be read like any other archive
+pub fn read_page(pdf: &Path, entry: &str) -> Result<Vec<u8>, ArchiveError> {
+    let img = render_page(pdf, entry)?;
+    let mut buf = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut buf, PAGE_JPEG_QUALITY);
+    DynamicImage::ImageRgb8(img.to_rgb8())
+        .write_with_encoder(encoder)
+        .map_err(|e| ArchiveError::Decoder(e.to_string()))?;
+    Ok(buf)
+}
+
+/// the size every page will have once it's rendered, in page order, without rendering them
+pub fn page_sizes(pdf: &Path) -> Result<Vec<(u32, u32)>, ArchiveError> {
+    render::page_sizes(pdf)
+}
+
+fn render_page(pdf: &Path, entry: &str) -> Result<DynamicImage, ArchiveError> {
+    let index = page_index(entry).ok_or_else(|| ArchiveError::EntryNotFound(entry.to_string()))?;
+    render::render_page(pdf, index)
+}
+
+/// rendered pages are cached next to the covers, keyed by the pdf's full path:
+/// `app_data/pdf/abcdef.../0001.jpg`
+fn format_pdf_page_path(app_data_dir: &Path, pdf: &Path, entry: &str) -> PathBuf {
+    let hash = blake3::hash(pdf.to_string_lossy().as_bytes()).to_hex();
+    app_data_dir.join("pdf").join(&hash[..32]).join(entry)
+}
+
+fn is_fresh(cached: &Path, source: &Path) -> bool {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    matches!((modified(cached), modified(source)), (Some(c), Some(s)) if c >= s)
+}
+
+fn cache_pdf_page(app_data_dir: &Path, path: &str) -> Result<String, ReadDirError> {
+    let (pdf, entry) =
+        split_archive_path(path).ok_or_else(|| ReadDirError::Path(path.to_string()))?;
+    let output = format_pdf_page_path(app_data_dir, &pdf, &entry);
+
+    if !is_fresh(&output, &pdf) {
+        let img = render_page(&pdf, &entry)?;
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let buf = BufWriter::new(File::create(&output)?);
+        let encoder = JpegEncoder::new_with_quality(buf, PAGE_JPEG_QUALITY);
+        DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)?;
+    }
+
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// renders a pdf page (if it isn't cached already) and returns the path of the cached image
+#[command]
+pub async fn get_pdf_page(handle: AppHandle, path: String) -> Result<String, ReadDirError> {
+    let app_data_dir = handle.path().app_data_dir()?;
+    tokio::task::spawn_blocking(move || cache_pdf_page(&app_data_dir, &path))
+        .await
+        .map_err(io::Error::other)?
+}
+
+mod render {
+    use std::{path::Path, sync::OnceLock};
+
+    use image::DynamicImage;
+    use pdfium_render::prelude::*;
+
+    use crate::error::ArchiveError;
+
+    const RENDER_WIDTH: i32 = 1600;
+    const RENDER_MAX_HEIGHT: i32 = 2400;
+
+    /// pdfium is initialized once for the whole process.
+    /// every `Pdfium` destroys the library when it's dropped, even while another thread is using it.
+    static PDFIUM: OnceLock<Result<Pdfium, String>> = OnceLock::new();
+
+    /// prefers a pdfium library shipped next to the executable,
+    /// and falls back to the one installed on the system
+    fn load_pdfium() -> Result<Pdfium, PdfiumError> {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+        let bindings = match exe_dir {
+            Some(dir) => {
+                Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&dir))
+                    .or_else(|_| Pdfium::bind_to_system_library())
+            }
+            None => Pdfium::bind_to_system_library(),
+        }?;
+
+        Ok(Pdfium::new(bindings))
+    }
+
+    fn pdfium() -> Result<&'static Pdfium, ArchiveError> {
+        PDFIUM
+            .get_or_init(|| load_pdfium().map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| ArchiveError::Decoder(e.clone()))
+    }
+
+    pub fn page_count(pdf: &Path) -> Result<u16, ArchiveError> {
+        let document = pdfium()?.load_pdf_from_file(pdf, None)?;
+        Ok(document.pages().len())
+    }
+
+    /// scales the pages the same way `render_page` does, the document is only opened once
+    pub fn page_sizes(pdf: &Path) -> Result<Vec<(u32, u32)>, ArchiveError> {
+        let document = pdfium()?.load_pdf_from_file(pdf, None)?;
+        let sizes = document
+            .pages()
+            .iter()
+            .map(|page| {
+                let (width, height) = (page.width().value, page.height().value);
+                let scale = (RENDER_WIDTH as f32 / width).min(RENDER_MAX_HEIGHT as f32 / height);
+                ((width * scale) as u32, (height * scale) as u32)
+            })
+            .collect();
+        Ok(sizes)
+    }
+
+    pub fn render_page(pdf: &Path, index: u16) -> Result<DynamicImage, ArchiveError> {
+        let document = pdfium()?.load_pdf_from_file(pdf, None)?;
+        let page = document.pages().get(index)?;
+
+        let config = PdfRenderConfig::new()
+            .set_target_width(RENDER_WIDTH)
+            .set_maximum_height(RENDER_MAX_HEIGHT);
+
+        let bitmap = page.render_with_config(&config)?;
+        Ok(bitmap.as_image())
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use lru::LruCache;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tauri::{command, ipc::Response, AppHandle, Manager};
+
+use crate::{
+    database::{data::v2::MangaPanel, Margins},
+    error::PrefetchError,
+    protocol::{page_etag, render_page, PageOptions, RenderedPage},
+    split::reader_panels,
+};
+
+/// decoded and resized pages are kept in memory up to this size
+const PANEL_CACHE_MAX_BYTES: usize = 256 * 1024 * 1024;
+const PREFETCH_THREADS: usize = 2;
+const DEFAULT_AHEAD: usize = 4;
+const DEFAULT_BEHIND: usize = 2;
+
+struct PanelCacheIndex {
+    entries: LruCache<String, Arc<RenderedPage>>,
+    total_bytes: usize,
+}
+
+/// the prefetch that is currently running, there's only ever one since there's only one reader
+struct Prefetch {
+    folder: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// pages the reader is about to show, keyed by their `page_etag`
+/// so a page that changed on disk is never served from here
+pub struct PanelCache {
+    index: Mutex<PanelCacheIndex>,
+    prefetch: Mutex<Option<Prefetch>>,
+    /// kept apart from the global pool so prefetching never holds up a scan
+    pool: ThreadPool,
+}
+
+impl PanelCache {
+    pub fn new() -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(PREFETCH_THREADS)
+            .thread_name(|i| format!("panel-prefetch-{i}"))
+            .build()?;
+
+        Ok(PanelCache {
+            index: Mutex::new(PanelCacheIndex {
+                entries: LruCache::unbounded(),
+                total_bytes: 0,
+            }),
+            prefetch: Mutex::new(None),
+            pool,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<RenderedPage>> {
+        self.index.lock().unwrap().entries.get(key).cloned()
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.index.lock().unwrap().entries.contains(key)
+    }
+
+    fn insert(&self, key: String, page: Arc<RenderedPage>) {
+        let mut index = self.index.lock().unwrap();
+        index.total_bytes += page.bytes.len();
+        if let Some(old) = index.entries.put(key, page) {
+            index.total_bytes -= old.bytes.len();
+        }
+
+        while index.total_bytes > PANEL_CACHE_MAX_BYTES {
+            let Some((_, page)) = index.entries.pop_lru() else {
+                break;
+            };
+            index.total_bytes -= page.bytes.len();
+        }
+    }
+
+    /// renders the page, or returns it straight from the cache
+    fn load(&self, path: &str, options: &PageOptions) -> Result<Arc<RenderedPage>, PrefetchError> {
+        let key = page_etag(path, options).ok_or(PrefetchError::PanelNotFound(path.to_string()))?;
+        if let Some(page) = self.get(&key) {
+            return Ok(page);
+        }
+
+        let page = Arc::new(render_page(path, options)?);
+        self.insert(key, page.clone());
+        Ok(page)
+    }
+
+    /// cancels the running prefetch and returns the flag of the new one
+    fn start_prefetch(&self, folder: &str) -> Arc<AtomicBool> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let previous = self.prefetch.lock().unwrap().replace(Prefetch {
+            folder: folder.to_string(),
+            cancelled: cancelled.clone(),
+        });
+        if let Some(previous) = previous {
+            previous.cancelled.store(true, Ordering::Relaxed);
+        }
+        cancelled
+    }
+
+    /// returns `false` if nothing was prefetching, or it was for another folder
+    fn cancel_prefetch(&self, folder: Option<&str>) -> bool {
+        let mut prefetch = self.prefetch.lock().unwrap();
+        if prefetch
+            .as_ref()
+            .is_some_and(|p| folder.is_none_or(|f| f == p.folder))
+        {
+            let prefetch = prefetch.take().unwrap();
+            prefetch.cancelled.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+}
+
+/// the panels around `index`, closest first, with the ones ahead before the ones behind
+fn prefetch_order(len: usize, index: usize, ahead: usize, behind: usize) -> Vec<usize> {
+    let mut order = Vec::with_capacity(ahead + behind);
+    for offset in 1..=ahead.max(behind) {
+        if offset <= ahead && index + offset < len {
+            order.push(index + offset);
+        }
+        if offset <= behind && offset <= index {
+            order.push(index - offset);
+        }
+    }
+    order
+}
+
+/// decodes, crops and resizes the `ahead` panels after `panel` and the `behind` panels before it
+/// in the background, so they're ready by the time the reader turns the page.
+/// replaces any prefetch that is still running.
+/// returns the amount of panels that were queued.
+#[command]
+pub async fn prefetch_panels(
+    handle: AppHandle,
+    panel: MangaPanel,
+    width: Option<u32>,
+    crop: Option<Margins>,
+    ahead: Option<usize>,
+    behind: Option<usize>,
+) -> Result<usize, PrefetchError> {
+    let options = PageOptions { width, crop };
+    tauri::async_runtime::spawn_blocking(move || {
+        queue_prefetch(&handle, &panel, options, ahead, behind)
+    })
+    .await?
+}
+
+fn queue_prefetch(
+    handle: &AppHandle,
+    panel: &MangaPanel,
+    options: PageOptions,
+    ahead: Option<usize>,
+    behind: Option<usize>,
+) -> Result<usize, PrefetchError> {
+    let panels = reader_panels(handle, &panel.parent_path)?;
+    let index = panels
+        .iter()
+        .position(|p| p.path == panel.path)
+        .ok_or(PrefetchError::PanelNotFound(panel.path.clone()))?;
+
+    let cache = handle.state::<PanelCache>();
+    let cancelled = cache.start_prefetch(&panel.parent_path);
+
+    let order = prefetch_order(
+        panels.len(),
+        index,
+        ahead.unwrap_or(DEFAULT_AHEAD),
+        behind.unwrap_or(DEFAULT_BEHIND),
+    );
+    let mut queued = 0;
+    for i in order {
+        let path = panels[i].path.clone();
+        if page_etag(&path, &options).is_some_and(|key| cache.contains(&key)) {
+            continue;
+        }
+
+        let handle = handle.clone();
+        let cancelled = cancelled.clone();
+        cache.pool.spawn_fifo(move || {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(e) = handle.state::<PanelCache>().load(&path, &options) {
+                eprintln!("failed to prefetch {path}: {e}");
+            }
+        });
+        queued += 1;
+    }
+
+    Ok(queued)
+}
+
+/// the bytes of a panel, cropped and resized to `width`. prefetched panels come from memory,
+/// anything else is rendered and cached on the spot.
+#[command]
+pub async fn get_cached_panel(
+    handle: AppHandle,
+    path: String,
+    width: Option<u32>,
+    crop: Option<Margins>,
+) -> Result<Response, PrefetchError> {
+    let options = PageOptions { width, crop };
+    let page = tauri::async_runtime::spawn_blocking(move || {
+        handle.state::<PanelCache>().load(&path, &options)
+    })
+    .await??;
+
+    Ok(Response::new(page.bytes.clone()))
+}
+
+/// stops prefetching for `folder`, or whatever is prefetching if no folder is passed.
+/// called by the reader when it leaves a folder.
+#[command]
+pub fn cancel_prefetch(handle: AppHandle, folder: Option<String>) -> bool {
+    handle
+        .state::<PanelCache>()
+        .cancel_prefetch(folder.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_closest_panels_first() {
+        assert_eq!(prefetch_order(10, 5, 3, 2), vec![6, 4, 7, 3, 8]);
+        assert_eq!(prefetch_order(10, 5, 1, 3), vec![6, 4, 3, 2]);
+    }
+
+    #[test]
+    fn stays_inside_the_folder() {
+        assert_eq!(prefetch_order(10, 0, 2, 2), vec![1, 2]);
+        assert_eq!(prefetch_order(10, 9, 2, 1), vec![8]);
+        assert_eq!(prefetch_order(3, 1, 4, 4), vec![2, 0]);
+        assert_eq!(prefetch_order(0, 0, 2, 2), Vec::<usize>::new());
+        assert_eq!(prefetch_order(10, 5, 0, 0), Vec::<usize>::new());
+    }
+}
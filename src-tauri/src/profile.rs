@@ -0,0 +1,95 @@
+use tauri::{command, AppHandle};
+
+use crate::{
+    database::{
+        data::v2::FolderReadingProfile, get_default_user, get_folder_reading_profile,
+        get_inherited_reading_profiles, get_user_by_id, save_folder_reading_profile,
+        ReadingProfile, ReadingProfileOverrides,
+    },
+    error::DatabaseError,
+    misc::now_timestamp,
+};
+
+impl ReadingProfileOverrides {
+    /// fills in what this profile leaves open from `parent`
+    fn inherit(self, parent: ReadingProfileOverrides) -> ReadingProfileOverrides {
+        ReadingProfileOverrides {
+            direction: self.direction.or(parent.direction),
+            fit_mode: self.fit_mode.or(parent.fit_mode),
+            page_gap: self.page_gap.or(parent.page_gap),
+            offset_cover: self.offset_cover.or(parent.offset_cover),
+        }
+    }
+}
+
+impl ReadingProfile {
+    fn with_overrides(self, overrides: ReadingProfileOverrides) -> ReadingProfile {
+        ReadingProfile {
+            direction: overrides.direction.unwrap_or(self.direction),
+            fit_mode: overrides.fit_mode.unwrap_or(self.fit_mode),
+            page_gap: overrides.page_gap.unwrap_or(self.page_gap),
+            offset_cover: overrides.offset_cover.unwrap_or(self.offset_cover),
+        }
+    }
+}
+
+/// the folder's own profile, filled in from its parents and then the default of its user
+pub fn resolve_reading_profile(
+    handle: &AppHandle,
+    folder_path: &str,
+) -> Result<ReadingProfile, DatabaseError> {
+    let (user_id, profiles) = get_inherited_reading_profiles(handle, folder_path)?;
+    let user_id = match user_id {
+        Some(user_id) => user_id,
+        None => get_default_user(handle.clone())?.id,
+    };
+
+    let overrides = profiles
+        .into_iter()
+        .fold(ReadingProfileOverrides::default(), |profile, parent| {
+            profile.inherit(parent)
+        });
+    let settings = get_user_by_id(handle.clone(), user_id)?.settings;
+
+    Ok(settings.reading_profile.with_overrides(overrides))
+}
+
+/// how the folder is read. every view asks this instead of working it out on its own.
+#[command]
+pub fn get_reading_profile(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<ReadingProfile, DatabaseError> {
+    resolve_reading_profile(&handle, &folder_path)
+}
+
+/// what the folder sets itself, without what it inherits
+#[command]
+pub fn get_folder_reading_profile_overrides(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<Option<FolderReadingProfile>, DatabaseError> {
+    get_folder_reading_profile(&handle, &folder_path)
+}
+
+/// sets the parts of the profile the folder and its child folders override,
+/// `None` makes it inherit everything again
+#[command]
+pub fn set_folder_reading_profile(
+    handle: AppHandle,
+    folder_path: String,
+    user_id: String,
+    profile: Option<ReadingProfileOverrides>,
+) -> Result<ReadingProfile, DatabaseError> {
+    let reading_profile = profile
+        .filter(|p| *p != ReadingProfileOverrides::default())
+        .map(|profile| FolderReadingProfile {
+            folder_path: folder_path.clone(),
+            user_id,
+            profile,
+            updated_at: now_timestamp(),
+        });
+
+    save_folder_reading_profile(&handle, &folder_path, reading_profile)?;
+    resolve_reading_profile(&handle, &folder_path)
+}
@@ -0,0 +1,481 @@
+use std::{
+    fs::{self, File},
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use fast_image_resize::{images::Image, FilterType, PixelType, ResizeAlg, ResizeOptions, Resizer};
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, ImageEncoder, RgbImage};
+use lru::LruCache;
+use tauri::{
+    http::{header, Request, Response, StatusCode},
+    AppHandle, Manager, UriSchemeContext, UriSchemeResponder, Url, Wry,
+};
+
+use crate::{
+    database::{FileMetadata, Margins},
+    error::MangaImageError,
+    fs::{decode_panel_image, read_panel_bytes},
+    prefetch::PanelCache,
+};
+
+/// the name of the custom protocol. pages are loaded from
+/// `shelf://localhost/page?path=...&w=1400&crop=0.02,0.03,0.02,0.03`,
+/// or `http://shelf.localhost/page?...` on windows
+pub const SHELF_SCHEME: &str = "shelf";
+
+/// the resized pages on disk are capped at this size, the least recently used ones go first
+const PAGE_CACHE_MAX_BYTES: u64 = 512 * 1024 * 1024;
+const PAGE_JPEG_QUALITY: u8 = 90;
+
+struct PageCacheIndex {
+    entries: LruCache<String, u64>,
+    total_bytes: u64,
+}
+
+/// resized pages, stored under `app_data/page_cache/{etag}.jpg`
+pub struct PageCache {
+    dir: PathBuf,
+    index: Mutex<PageCacheIndex>,
+}
+
+impl PageCache {
+    /// picks up the pages cached by earlier runs, ordered by when they were last used
+    pub fn load(app_data_dir: &Path) -> std::io::Result<Self> {
+        let dir = app_data_dir.join("page_cache");
+        fs::create_dir_all(&dir)?;
+
+        let mut files: Vec<(String, u64, SystemTime)> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let metadata = e.metadata().ok()?;
+                let name = e.file_name().to_string_lossy().to_string();
+                Some((name, metadata.len(), metadata.modified().ok()?))
+            })
+            .collect();
+        files.sort_by_key(|(_, _, modified)| *modified);
+
+        let cache = PageCache {
+            dir,
+            index: Mutex::new(PageCacheIndex {
+                entries: LruCache::unbounded(),
+                total_bytes: 0,
+            }),
+        };
+
+        let mut index = cache.index.lock().unwrap();
+        for (name, size, _) in files {
+            index.entries.put(name, size);
+            index.total_bytes += size;
+        }
+        cache.evict(&mut index);
+        drop(index);
+
+        Ok(cache)
+    }
+
+    /// the index is only locked to look the page up, so reading one page doesn't hold up the others
+    fn get(&self, name: &str) -> Option<Vec<u8>> {
+        self.index.lock().unwrap().entries.get(name)?;
+
+        let path = self.dir.join(name);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                // the modified time is what orders the cache on the next launch
+                if let Ok(file) = File::options().write(true).open(&path) {
+                    file.set_modified(SystemTime::now()).ok();
+                }
+                Some(bytes)
+            }
+            Err(_) => {
+                let mut index = self.index.lock().unwrap();
+                if let Some(size) = index.entries.pop(name) {
+                    index.total_bytes -= size;
+                }
+                None
+            }
+        }
+    }
+
+    fn insert(&self, name: &str, bytes: &[u8]) -> std::io::Result<()> {
+        // written next to the real file first, so a half written page is never served
+        let path = self.dir.join(name);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path)?;
+
+        let mut index = self.index.lock().unwrap();
+        if let Some(old) = index.entries.put(name.to_string(), bytes.len() as u64) {
+            index.total_bytes -= old;
+        }
+        index.total_bytes += bytes.len() as u64;
+        self.evict(&mut index);
+
+        Ok(())
+    }
+
+    fn evict(&self, index: &mut PageCacheIndex) {
+        while index.total_bytes > PAGE_CACHE_MAX_BYTES {
+            let Some((name, size)) = index.entries.pop_lru() else {
+                break;
+            };
+            index.total_bytes -= size;
+            fs::remove_file(self.dir.join(name)).ok();
+        }
+    }
+}
+
+/// how a page is changed before it's served
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageOptions {
+    /// downscales the page to this width, if it's any wider
+    pub width: Option<u32>,
+    pub crop: Option<Margins>,
+}
+
+impl PageOptions {
+    fn is_original(&self) -> bool {
+        self.width.is_none() && self.crop.is_none()
+    }
+}
+
+struct PageRequest {
+    path: String,
+    options: PageOptions,
+}
+
+impl PageRequest {
+    fn from_uri(uri: &str) -> Option<Self> {
+        let url = Url::parse(uri).ok()?;
+        let mut path = None;
+        let mut options = PageOptions::default();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "path" => path = Some(value.to_string()),
+                "w" => options.width = value.parse().ok().filter(|w| *w > 0),
+                "crop" => options.crop = Margins::from_query(&value),
+                _ => {}
+            }
+        }
+
+        Some(PageRequest {
+            path: path?,
+            options,
+        })
+    }
+}
+
+/// changes whenever the source page is modified, or it's requested with different options.
+/// `None` if the page doesn't exist.
+pub fn page_etag(path: &str, options: &PageOptions) -> Option<String> {
+    let metadata = FileMetadata::from_path(path)?;
+    let modified = metadata
+        .modified
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(path.as_bytes());
+    hasher.update(&modified.to_le_bytes());
+    hasher.update(&metadata.size.unwrap_or_default().to_le_bytes());
+    hasher.update(&options.width.unwrap_or_default().to_le_bytes());
+    if let Some(crop) = options.crop {
+        for side in [crop.top, crop.right, crop.bottom, crop.left] {
+            hasher.update(&side.to_le_bytes());
+        }
+    }
+    Some(hasher.finalize().to_hex()[..32].to_string())
+}
+
+fn content_type(path: &str) -> &'static str {
+    let ext = Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" | "jfif" => "image/jpeg",
+        "png" | "apng" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "heif" => "image/heif",
+        _ => "application/octet-stream",
+    }
+}
+
+/// downscales a page to `width`, keeping its aspect ratio
+fn resize_image(img: &DynamicImage, width: u32) -> Result<RgbImage, MangaImageError> {
+    let height = ((img.height() as u64 * width as u64) / img.width() as u64).max(1) as u32;
+    let src = DynamicImage::ImageRgb8(img.to_rgb8());
+    let mut dst = Image::new(width, height, PixelType::U8x3);
+
+    let options = ResizeOptions::new().resize_alg(ResizeAlg::Convolution(FilterType::Lanczos3));
+    Resizer::new().resize(&src, &mut dst, &options)?;
+
+    RgbImage::from_raw(width, height, dst.into_vec()).ok_or_else(|| {
+        MangaImageError::InvalidPixelType(String::from("resized page has the wrong size"))
+    })
+}
+
+fn encode_page(img: &RgbImage) -> Result<Vec<u8>, MangaImageError> {
+    let mut buf = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buf, PAGE_JPEG_QUALITY).write_image(
+        img.as_raw(),
+        img.width(),
+        img.height(),
+        image::ExtendedColorType::Rgb8,
+    )?;
+
+    Ok(buf.into_inner())
+}
+
+/// a page the way the webview gets it
+pub struct RenderedPage {
+    pub bytes: Vec<u8>,
+    pub content_type: &'static str,
+    /// `false` if these are the bytes of the original file
+    pub resized: bool,
+}
+
+/// reads a page, crops its margins and downscales it to the requested width if it's any wider.
+/// a page that doesn't need either is served as is.
+pub fn render_page(path: &str, options: &PageOptions) -> Result<RenderedPage, MangaImageError> {
+    let bytes = read_panel_bytes(path)?;
+    let original = |bytes| RenderedPage {
+        bytes,
+        content_type: content_type(path),
+        resized: false,
+    };
+    if options.is_original() {
+        return Ok(original(bytes));
+    }
+
+    // a page that's already narrow enough doesn't have to be decoded to find that out
+    let is_narrow =
+        |width| imagesize::blob_size(&bytes).is_ok_and(|size| size.width as u32 <= width);
+    if options.crop.is_none() && options.width.is_some_and(is_narrow) {
+        return Ok(original(bytes));
+    }
+
+    let mut img = decode_panel_image(bytes.clone())?;
+    if let Some(crop) = options.crop {
+        img = crop.crop(&img);
+    }
+
+    let page = match options.width {
+        Some(width) if img.width() > width => resize_image(&img, width)?,
+        _ if options.crop.is_some() => img.to_rgb8(),
+        _ => return Ok(original(bytes)),
+    };
+
+    Ok(RenderedPage {
+        bytes: encode_page(&page)?,
+        content_type: "image/jpeg",
+        resized: true,
+    })
+}
+
+/// parses a single `bytes=start-end` range. `Err` means the range can't be satisfied.
+fn parse_range(header: &str, len: usize) -> Result<(usize, usize), ()> {
+    let range = header.strip_prefix("bytes=").ok_or(())?;
+    // multiple ranges would need a multipart response, so only the first one is served
+    let range = range.split(',').next().ok_or(())?.trim();
+    let (start, end) = range.split_once('-').ok_or(())?;
+
+    let (start, end) = match (start.parse::<usize>(), end.parse::<usize>()) {
+        (Ok(start), Ok(end)) => (start, end.min(len.saturating_sub(1))),
+        (Ok(start), Err(_)) if end.is_empty() => (start, len.saturating_sub(1)),
+        // `bytes=-500` is the last 500 bytes
+        (Err(_), Ok(suffix)) if start.is_empty() && suffix > 0 => {
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        _ => return Err(()),
+    };
+
+    if start > end || start >= len {
+        return Err(());
+    }
+    Ok((start, end))
+}
+
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Vec::new())
+        .unwrap()
+}
+
+fn page_response(
+    request: &Request<Vec<u8>>,
+    body: Vec<u8>,
+    content_type: &str,
+    etag: &str,
+) -> Response<Vec<u8>> {
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ETAG, etag)
+        // the etag changes with the page, so the webview only has to revalidate
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|r| r.to_str().ok());
+    let response = match range.map(|r| parse_range(r, body.len())) {
+        None => builder.status(StatusCode::OK).body(body),
+        Some(Ok((start, end))) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{end}/{}", body.len()),
+            )
+            .body(body[start..=end].to_vec()),
+        Some(Err(())) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", body.len()))
+            .body(Vec::new()),
+    };
+
+    response.unwrap()
+}
+
+fn serve_page(
+    handle: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, MangaImageError> {
+    let Some(page) = PageRequest::from_uri(&request.uri().to_string()) else {
+        return Ok(empty_response(StatusCode::BAD_REQUEST));
+    };
+    let Some(tag) = page_etag(&page.path, &page.options) else {
+        return Ok(empty_response(StatusCode::NOT_FOUND));
+    };
+
+    let etag = format!("\"{tag}\"");
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok());
+    if if_none_match.is_some_and(|tags| tags.split(',').any(|t| t.trim() == etag)) {
+        let mut response = empty_response(StatusCode::NOT_MODIFIED);
+        response
+            .headers_mut()
+            .insert(header::ETAG, etag.parse().unwrap());
+        return Ok(response);
+    }
+
+    // pages the reader prefetched are already in memory
+    if let Some(panel) = handle
+        .try_state::<PanelCache>()
+        .and_then(|cache| cache.get(&tag))
+    {
+        return Ok(page_response(
+            request,
+            panel.bytes.clone(),
+            panel.content_type,
+            &etag,
+        ));
+    }
+
+    let cache = handle.try_state::<PageCache>();
+    let cache_name = format!("{tag}.jpg");
+    if let Some(bytes) = cache.as_ref().and_then(|c| c.get(&cache_name)) {
+        return Ok(page_response(request, bytes, "image/jpeg", &etag));
+    }
+
+    let page = render_page(&page.path, &page.options)?;
+    if let Some(cache) = cache.filter(|_| page.resized) {
+        if let Err(e) = cache.insert(&cache_name, &page.bytes) {
+            eprintln!("failed to cache {}: {e}", request.uri());
+        }
+    }
+    Ok(page_response(request, page.bytes, page.content_type, &etag))
+}
+
+/// decoding and resizing is slow, so pages are served off of the main thread
+pub fn handle_shelf_request(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let handle = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = serve_page(&handle, &request).unwrap_or_else(|e| {
+            eprintln!("{}: {e}", request.uri());
+            empty_response(StatusCode::INTERNAL_SERVER_ERROR)
+        });
+        responder.respond(response);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Ok((500, 999)));
+        assert_eq!(parse_range("bytes=-200", 1000), Ok((800, 999)));
+        // the end is clamped to the body
+        assert_eq!(parse_range("bytes=900-5000", 1000), Ok((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Ok((0, 999)));
+        // only the first of multiple ranges is served
+        assert_eq!(parse_range("bytes=0-9, 20-29", 1000), Ok((0, 9)));
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(()));
+        assert_eq!(parse_range("bytes=50-10", 1000), Err(()));
+        assert_eq!(parse_range("bytes=-0", 1000), Err(()));
+        assert_eq!(parse_range("bytes=0-0", 0), Err(()));
+        assert_eq!(parse_range("bytes=a-b", 1000), Err(()));
+        assert_eq!(parse_range("bytes=-", 1000), Err(()));
+        assert_eq!(parse_range("items=0-99", 1000), Err(()));
+    }
+
+    #[test]
+    fn etag_follows_the_page_and_options() {
+        let path = std::env::temp_dir().join(format!("shelf-etag-{}.jpg", std::process::id()));
+        fs::write(&path, b"page").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let original = PageOptions::default();
+        let resized = PageOptions {
+            width: Some(1400),
+            ..original
+        };
+        let cropped = PageOptions {
+            crop: Some(Margins {
+                top: 0.02,
+                right: 0.03,
+                bottom: 0.02,
+                left: 0.03,
+            }),
+            ..resized
+        };
+
+        let tag = page_etag(&path_str, &original).unwrap();
+        assert_eq!(page_etag(&path_str, &original), Some(tag.clone()));
+        assert_ne!(page_etag(&path_str, &resized), Some(tag.clone()));
+        assert_ne!(
+            page_etag(&path_str, &cropped),
+            page_etag(&path_str, &resized)
+        );
+
+        // rewriting the page with a different size changes its tag
+        fs::write(&path, b"a different page").unwrap();
+        assert_ne!(page_etag(&path_str, &original), Some(tag));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(page_etag(&path_str, &original), None);
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use native_db::{
+    db_type,
+    transaction::{RTransaction, RwTransaction},
+    Database, Models, ToInput, ToKey,
+};
+use tauri::{Manager, Runtime, State};
+
+/// the database every command shares, opened once by `init_database`.
+/// reopening the redb file on each invoke failed whenever two commands raced for its lock.
+pub struct Repository {
+    db: Database<'static>,
+    models: &'static Models,
+}
+
+impl Repository {
+    pub fn new(db: Database<'static>, models: &'static Models) -> Self {
+        Repository { db, models }
+    }
+
+    /// a snapshot of what's been committed, any number of them run alongside each other and a write
+    pub fn read(&self) -> db_type::Result<RTransaction<'_>> {
+        self.db.r_transaction()
+    }
+
+    /// writes run one at a time, a write waits for the one before it to commit or drop.
+    /// don't start another write on the same thread while this one is alive, it would wait forever.
+    pub fn write(&self) -> db_type::Result<RwTransaction<'_>> {
+        self.db.rw_transaction()
+    }
+
+    pub fn get<T: ToInput>(&self, key: impl ToKey) -> db_type::Result<Option<T>> {
+        self.read()?.get().primary(key)
+    }
+
+    pub fn upsert<T: ToInput>(&self, item: T) -> db_type::Result<()> {
+        self.upsert_all([item])
+    }
+
+    /// upserts every item in a single transaction
+    pub fn upsert_all<T: ToInput>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+    ) -> db_type::Result<()> {
+        let rwtx = self.write()?;
+        for item in items {
+            rwtx.upsert(item)?;
+        }
+        rwtx.commit()
+    }
+
+    /// copies everything that's been committed into a new database file,
+    /// writes can keep going while it's copied
+    pub fn snapshot(&self, path: &Path) -> db_type::Result<()> {
+        self.db.snapshot(self.models, path)?;
+        Ok(())
+    }
+}
+
+/// `handle.repo()` on anything that can reach the app state
+pub trait RepositoryExt<R: Runtime> {
+    fn repo(&self) -> State<'_, Repository>;
+}
+
+impl<R: Runtime, M: Manager<R>> RepositoryExt<R> for M {
+    fn repo(&self) -> State<'_, Repository> {
+        self.state::<Repository>()
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use hashbrown::HashMap;
+use serde::Serialize;
+use tauri::{command, AppHandle, Emitter, Manager};
+
+use crate::error::ReadDirError;
+
+/// emitted with a `ScanProgress` while a folder is being read
+pub const SCAN_PROGRESS_EVENT: &str = "scan_progress";
+
+/// emitted with a `SkippedFolder` when a folder inside the scan couldn't be read
+pub const SCAN_SKIPPED_EVENT: &str = "scan_skipped";
+
+/// progress events are throttled so big libraries don't flood the frontend
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanStage {
+    Reading,
+    CompressingCovers,
+    Saving,
+    Finished,
+    Cancelled,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScanProgress {
+    /// the folder that was passed to `upsert_read_os_dir`
+    pub root: String,
+    pub stage: ScanStage,
+    pub folders_discovered: usize,
+    pub panels_indexed: usize,
+    pub covers_compressed: usize,
+    pub current_path: String,
+}
+
+/// a folder or archive the scan left out, the rest of the scan goes on without it
+#[derive(Serialize, Clone, Debug)]
+pub struct SkippedFolder {
+    pub root: String,
+    pub path: String,
+    pub reason: String,
+}
+
+/// a running scan, shared with the rayon threads that read its folders
+pub struct Scan {
+    handle: AppHandle,
+    root: String,
+    cancelled: AtomicBool,
+    folders_discovered: AtomicUsize,
+    panels_indexed: AtomicUsize,
+    covers_compressed: AtomicUsize,
+    last_emit: Mutex<(Instant, String)>,
+}
+
+/// every running scan by its root path, so `cancel_scan` can find them
+#[derive(Default)]
+pub struct ScanRegistry(Mutex<HashMap<String, Arc<Scan>>>);
+
+/// removes the scan from the registry once it finishes, fails or gets cancelled
+pub struct ScanGuard(Arc<Scan>);
+
+impl std::ops::Deref for ScanGuard {
+    type Target = Scan;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        if let Some(registry) = self.0.handle.try_state::<ScanRegistry>() {
+            let mut scans = registry.0.lock().unwrap();
+            if scans
+                .get(&self.0.root)
+                .is_some_and(|scan| Arc::ptr_eq(scan, &self.0))
+            {
+                scans.remove(&self.0.root);
+            }
+        }
+    }
+}
+
+impl Scan {
+    pub fn start(handle: &AppHandle, root: &str) -> ScanGuard {
+        let scan = Arc::new(Scan {
+            handle: handle.clone(),
+            root: root.to_string(),
+            cancelled: AtomicBool::new(false),
+            folders_discovered: AtomicUsize::new(0),
+            panels_indexed: AtomicUsize::new(0),
+            covers_compressed: AtomicUsize::new(0),
+            last_emit: Mutex::new((Instant::now(), root.to_string())),
+        });
+
+        if let Some(registry) = handle.try_state::<ScanRegistry>() {
+            registry
+                .0
+                .lock()
+                .unwrap()
+                .insert(root.to_string(), scan.clone());
+        }
+
+        scan.emit(ScanStage::Reading);
+        ScanGuard(scan)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// bails out of the scan if it was cancelled
+    pub fn check_cancelled(&self) -> Result<(), ReadDirError> {
+        if self.is_cancelled() {
+            return Err(ReadDirError::ScanCancelled(self.root.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn folder_read(&self, path: &str, panels: usize) {
+        self.folders_discovered.fetch_add(1, Ordering::Relaxed);
+        self.panels_indexed.fetch_add(panels, Ordering::Relaxed);
+        self.report(ScanStage::Reading, path);
+    }
+
+    pub fn cover_compressed(&self, path: &str) {
+        self.covers_compressed.fetch_add(1, Ordering::Relaxed);
+        self.report(ScanStage::CompressingCovers, path);
+    }
+
+    /// tells the frontend a folder was left out of the scan
+    pub fn folder_skipped(&self, path: &str, error: &ReadDirError) {
+        let skipped = SkippedFolder {
+            root: self.root.clone(),
+            path: path.to_string(),
+            reason: error.to_string(),
+        };
+
+        if let Err(e) = self.handle.emit(SCAN_SKIPPED_EVENT, skipped) {
+            eprintln!("{e}");
+        }
+    }
+
+    /// emits a progress event, unless one was sent in the last `PROGRESS_INTERVAL`
+    fn report(&self, stage: ScanStage, path: &str) {
+        let mut last_emit = self.last_emit.lock().unwrap();
+        last_emit.1 = path.to_string();
+        if last_emit.0.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        last_emit.0 = Instant::now();
+        drop(last_emit);
+
+        self.emit(stage);
+    }
+
+    /// always emits, used when the stage changes
+    pub fn emit(&self, stage: ScanStage) {
+        let current_path = self.last_emit.lock().unwrap().1.clone();
+        let progress = ScanProgress {
+            root: self.root.clone(),
+            stage,
+            folders_discovered: self.folders_discovered.load(Ordering::Relaxed),
+            panels_indexed: self.panels_indexed.load(Ordering::Relaxed),
+            covers_compressed: self.covers_compressed.load(Ordering::Relaxed),
+            current_path,
+        };
+
+        if let Err(e) = self.handle.emit(SCAN_PROGRESS_EVENT, progress) {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// stops the scan of `path`, or every running scan if no path is passed.
+/// nothing gets written to the database once a scan is cancelled.
+/// returns `false` if there was no scan to cancel.
+#[command]
+pub fn cancel_scan(handle: AppHandle, path: Option<String>) -> bool {
+    let registry = handle.state::<ScanRegistry>();
+    let scans = registry.0.lock().unwrap();
+
+    let mut cancelled = false;
+    for (root, scan) in scans.iter() {
+        if path.as_ref().is_none_or(|p| p == root) {
+            scan.cancel();
+            cancelled = true;
+        }
+    }
+
+    cancelled
+}
@@ -0,0 +1,504 @@
+use std::{cmp::Ordering, path::Path, sync::LazyLock};
+
+use regex::Regex;
+
+/// a bare `v` is only a volume in front of a chapter, `v02 c010`.
+/// elsewhere it's a release tag, `c001 [v2]`.
+pub static VOLUME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:\bvol(?:ume)?\.?\s*(\d+))|(?:第?\s*(\d+)\s*巻)|(?:\bv\.?\s*(\d+)[\s._\-]*(?:ch|c|ep|#))",
+    )
+    .unwrap()
+});
+
+pub static CHAPTER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:(?:\b(?:ch(?:apter|ap)?|ep(?:isode)?|c)|#)\.?\s*(\d+)(?:[.,](\d+))?)|(?:第?\s*(\d+)(?:\.(\d+))?\s*[話章])",
+    )
+    .unwrap()
+});
+
+pub static NUMBER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+)(?:\.(\d+))?").unwrap());
+
+pub static PART_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bpart\s*(\d+)").unwrap());
+
+/// `v2` after a chapter, `Title 012 [v2]`, so it isn't picked up as the chapter
+static RELEASE_TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bv\d+\b").unwrap());
+
+const KANJI_NUMERALS: &str = "〇零一壱二弐两三参四五六七八九十百千万";
+
+/// kanji numbers next to a counter: `第十二話`, `百話`, `第二十三巻`
+static KANJI_NUMBER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"第([{KANJI_NUMERALS}]+)|([{KANJI_NUMERALS}]+)([話巻章節回])"
+    ))
+    .unwrap()
+});
+
+/// `(上)`, `【下】`, `「中巻」`
+static BRACKETED_PART_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[（(【\[「]\s*([上中下])巻?\s*[）)】\]」]").unwrap());
+
+/// `上巻`, `下巻`
+static PART_VOLUME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([上中下])巻").unwrap());
+
+/// `第1巻 上`, `1巻下`
+static TRAILING_PART_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"巻\s*([上中下])(?:$|[\s._\-])").unwrap());
+
+/// volume, chapter and sub-chapter parsed out of a folder or panel title.
+/// missing parts sort before present ones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChapterKey {
+    pub volume: Option<u64>,
+    pub chapter: Option<u64>,
+    /// the decimal part of the chapter, `Ch 10.5` -> `0.5`
+    pub sub_chapter: Option<f64>,
+    /// `Part 2`, or the `上/中/下` split of a volume
+    pub part: Option<u32>,
+}
+
+impl Eq for ChapterKey {}
+
+impl PartialOrd for ChapterKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ChapterKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.volume
+            .cmp(&other.volume)
+            .then(self.chapter.cmp(&other.chapter))
+            .then_with(|| match (self.sub_chapter, other.sub_chapter) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+            .then(self.part.cmp(&other.part))
+    }
+}
+
+fn parse_sub_chapter(digits: Option<regex::Match>) -> Option<f64> {
+    digits.and_then(|d| format!("0.{}", d.as_str()).parse().ok())
+}
+
+/// drops a file extension, but keeps decimals like `Ch 10.5`
+fn strip_extension(title: &str) -> &str {
+    match Path::new(title).extension().and_then(|e| e.to_str()) {
+        Some(ext)
+            if ext.len() <= 5
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+                && ext.chars().any(|c| c.is_ascii_alphabetic()) =>
+        {
+            &title[..title.len() - ext.len() - 1]
+        }
+        _ => title,
+    }
+}
+
+impl ChapterKey {
+    pub fn parse(title: &str) -> Self {
+        let title = normalize_numerals(strip_extension(title));
+        let mut key = ChapterKey::default();
+
+        // remove the part and volume so their numbers can't be picked up as the chapter
+        let title = match PART_REGEX.captures(&title) {
+            Some(caps) => {
+                key.part = caps.get(1).and_then(|m| m.as_str().parse().ok());
+                let range = caps.get(0).unwrap().range();
+                format!("{} {}", &title[..range.start], &title[range.end..])
+            }
+            None => title,
+        };
+
+        let rest = match VOLUME_REGEX.captures(&title) {
+            Some(caps) => {
+                key.volume = caps
+                    .get(1)
+                    .or_else(|| caps.get(2))
+                    .or_else(|| caps.get(3))
+                    .and_then(|m| m.as_str().parse().ok());
+                let start = caps.get(0).unwrap().start();
+                // `v02 c010` keeps the chapter marker that follows the volume
+                let end = caps.get(3).unwrap_or_else(|| caps.get(0).unwrap()).end();
+                format!("{} {}", &title[..start], &title[end..])
+            }
+            None => title.to_string(),
+        };
+
+        if let Some(caps) = CHAPTER_REGEX.captures(&rest) {
+            key.chapter = caps
+                .get(1)
+                .or_else(|| caps.get(3))
+                .and_then(|m| m.as_str().parse().ok());
+            key.sub_chapter = parse_sub_chapter(caps.get(2).or_else(|| caps.get(4)));
+        } else if let Some(caps) = NUMBER_REGEX
+            .captures_iter(&RELEASE_TAG_REGEX.replace_all(&rest, " "))
+            .last()
+        {
+            // no marker, so the last number is most likely the chapter: `One Piece 1012`
+            key.chapter = caps.get(1).and_then(|m| m.as_str().parse().ok());
+            key.sub_chapter = parse_sub_chapter(caps.get(2));
+        }
+
+        key
+    }
+}
+
+fn kanji_digit(c: char) -> Option<u64> {
+    match c {
+        '〇' | '零' => Some(0),
+        '一' | '壱' => Some(1),
+        '二' | '弐' | '两' => Some(2),
+        '三' | '参' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    }
+}
+
+/// converts both multiplicative (`二十三`, `百五`) and positional (`二〇二四`) kanji numbers
+pub fn parse_kanji_number(kanji: &str) -> Option<u64> {
+    let mut total = 0;
+    let mut section = 0;
+    let mut digits: Option<u64> = None;
+
+    for c in kanji.chars() {
+        if let Some(d) = kanji_digit(c) {
+            digits = Some(digits.unwrap_or(0) * 10 + d);
+            continue;
+        }
+
+        let unit = match c {
+            '十' => 10,
+            '百' => 100,
+            '千' => 1000,
+            '万' => {
+                total += (section + digits.unwrap_or(0)).max(1) * 10_000;
+                section = 0;
+                digits = None;
+                continue;
+            }
+            _ => return None,
+        };
+        section += digits.unwrap_or(1) * unit;
+        digits = None;
+    }
+
+    Some(total + section + digits.unwrap_or(0))
+}
+
+fn part_number(marker: &str) -> u32 {
+    match marker {
+        "上" => 1,
+        "中" => 2,
+        _ => 3,
+    }
+}
+
+/// rewrites full-width characters, kanji numbers and `上/中/下` volume markers
+/// into something the ascii regexes above understand:
+/// `第十二話` -> `第12話`, `第１２話` -> `第12話`, `第3巻 (下)` -> `第3巻  part 3 `
+pub fn normalize_numerals(title: &str) -> String {
+    // full-width ascii (`１２`, `ｖｏｌ．`) lives at a fixed offset from ascii
+    let title: String = title
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            c => c,
+        })
+        .collect();
+
+    let title = KANJI_NUMBER_REGEX.replace_all(&title, |caps: &regex::Captures| {
+        match (caps.get(1), caps.get(2), caps.get(3)) {
+            (Some(n), ..) => match parse_kanji_number(n.as_str()) {
+                Some(n) => format!("第{n}"),
+                None => caps[0].to_string(),
+            },
+            (None, Some(n), Some(counter)) => match parse_kanji_number(n.as_str()) {
+                Some(n) => format!("{n}{}", counter.as_str()),
+                None => caps[0].to_string(),
+            },
+            _ => caps[0].to_string(),
+        }
+    });
+
+    let title = BRACKETED_PART_REGEX.replace_all(&title, |caps: &regex::Captures| {
+        format!(" part {} ", part_number(&caps[1]))
+    });
+    let title = TRAILING_PART_REGEX.replace_all(&title, |caps: &regex::Captures| {
+        format!("巻 part {} ", part_number(&caps[1]))
+    });
+    let title = PART_VOLUME_REGEX.replace_all(&title, |caps: &regex::Captures| {
+        format!(" part {} ", part_number(&caps[1]))
+    });
+
+    title.into_owned()
+}
+
+/// digit-aware string compare, so `page2` comes before `page10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
+                let take_digits = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+                let a_num = take_digits(&mut a_chars);
+                let b_num = take_digits(&mut b_chars);
+                let a_trimmed = a_num.trim_start_matches('0');
+                let b_trimmed = b_num.trim_start_matches('0');
+
+                // compare by length first so numbers of any size work
+                let ord = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(ac), Some(bc)) => {
+                let ord = ac.to_lowercase().cmp(bc.to_lowercase());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// orders titles by volume, chapter and sub-chapter,
+/// falling back to a natural compare when those are the same or missing
+pub fn chapter_cmp(a: &str, b: &str) -> Ordering {
+    ChapterKey::parse(a)
+        .cmp(&ChapterKey::parse(b))
+        .then_with(|| natural_cmp(a, b))
+}
+
+/// what `chapter_cmp` compares, parsed once per title for `sort_by_cached_key`
+/// instead of on every compare
+#[derive(Debug, Clone)]
+pub struct ChapterSortKey {
+    key: ChapterKey,
+    title: String,
+}
+
+impl ChapterSortKey {
+    pub fn new(title: &str) -> Self {
+        ChapterSortKey {
+            key: ChapterKey::parse(title),
+            title: title.to_string(),
+        }
+    }
+}
+
+impl PartialEq for ChapterSortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ChapterSortKey {}
+
+impl PartialOrd for ChapterSortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ChapterSortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then_with(|| natural_cmp(&self.title, &other.title))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (title, volume, chapter, sub chapter, part)
+    type Case = (
+        &'static str,
+        Option<u64>,
+        Option<u64>,
+        Option<f64>,
+        Option<u32>,
+    );
+
+    const TITLES: &[Case] = &[
+        ("Vol 2 Ch 10", Some(2), Some(10), None, None),
+        ("Vol.2 Chapter 10.cbz", Some(2), Some(10), None, None),
+        ("Volume 12", Some(12), None, None, None),
+        ("Ch 10.5", None, Some(10), Some(0.5), None),
+        ("Chapter 10,5", None, Some(10), Some(0.5), None),
+        ("Chapter 1012", None, Some(1012), None, None),
+        ("c1000.cbz", None, Some(1000), None, None),
+        ("One Piece 1012", None, Some(1012), None, None),
+        ("v02 c010", Some(2), Some(10), None, None),
+        ("v3c21", Some(3), Some(21), None, None),
+        ("[Group] Title - c001 [v2]", None, Some(1), None, None),
+        ("Ch 12 v2", None, Some(12), None, None),
+        ("Title 012 [v2]", None, Some(12), None, None),
+        ("Vol 1 Part 2", Some(1), None, None, Some(2)),
+        ("Oneshot", None, None, None, None),
+    ];
+
+    const JAPANESE_TITLES: &[Case] = &[
+        ("第十二話", None, Some(12), None, None),
+        ("第百話", None, Some(100), None, None),
+        ("第１２話", None, Some(12), None, None),
+        ("第二〇話", None, Some(20), None, None),
+        ("第百二十三話.cbz", None, Some(123), None, None),
+        ("第千二百三十四話", None, Some(1234), None, None),
+        ("ワンピース 第1012話", None, Some(1012), None, None),
+        ("【第３話】はじまりの日", None, Some(3), None, None),
+        ("第10.5話 おまけ", None, Some(10), Some(0.5), None),
+        ("進撃の巨人 第1巻", Some(1), None, None, None),
+        ("鬼滅の刃 第二十三巻", Some(23), None, None, None),
+        ("呪術廻戦 ２巻 第１０話", Some(2), Some(10), None, None),
+        (
+            "ＳＰＹ×ＦＡＭＩＬＹ ｖｏｌ．０５",
+            Some(5),
+            None,
+            None,
+            None,
+        ),
+        ("ドラゴンボール 上巻", None, None, None, Some(1)),
+        ("ドラゴンボール 下巻", None, None, None, Some(3)),
+        ("第3巻 (上)", Some(3), None, None, Some(1)),
+        ("第3巻【下】", Some(3), None, None, Some(3)),
+        ("第1巻 中", Some(1), None, None, Some(2)),
+        ("一番星 第三話", None, Some(3), None, None),
+        ("番外編", None, None, None, None),
+    ];
+
+    fn assert_parses(cases: &[Case]) {
+        for &(title, volume, chapter, sub_chapter, part) in cases {
+            let key = ChapterKey::parse(title);
+            assert_eq!(
+                key,
+                ChapterKey {
+                    volume,
+                    chapter,
+                    sub_chapter,
+                    part,
+                },
+                "{title}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_titles() {
+        assert_parses(TITLES);
+    }
+
+    #[test]
+    fn parses_japanese_titles() {
+        assert_parses(JAPANESE_TITLES);
+    }
+
+    #[test]
+    fn parses_kanji_numbers() {
+        let cases = [
+            ("〇", 0),
+            ("十", 10),
+            ("十二", 12),
+            ("二十", 20),
+            ("百五", 105),
+            ("二〇二四", 2024),
+            ("千二百三十四", 1234),
+            ("三万", 30_000),
+            ("一万二千", 12_000),
+        ];
+        for (kanji, expected) in cases {
+            assert_eq!(parse_kanji_number(kanji), Some(expected), "{kanji}");
+        }
+        assert_eq!(parse_kanji_number("話"), None);
+    }
+
+    #[test]
+    fn sorts_titles() {
+        let mut titles = vec![
+            "Vol 2 Ch 10",
+            "Ch 12 v2",
+            "Ch 10.5",
+            "Vol 1 Ch 3",
+            "Ch 10",
+            "Chapter 1000",
+            "Ch 99",
+        ];
+        titles.sort_by(|a, b| chapter_cmp(a, b));
+        assert_eq!(
+            titles,
+            [
+                "Ch 10",
+                "Ch 10.5",
+                "Ch 12 v2",
+                "Ch 99",
+                "Chapter 1000",
+                "Vol 1 Ch 3",
+                "Vol 2 Ch 10"
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_key_matches_chapter_cmp() {
+        let titles = [
+            "Vol 2 Ch 10",
+            "Extra 2",
+            "Ch 10.5",
+            "Extra 10",
+            "Vol 1 Part 2",
+            "Oneshot",
+            "第十話",
+            "Ch 10",
+        ];
+        let mut by_cmp = titles.to_vec();
+        by_cmp.sort_by(|a, b| chapter_cmp(a, b));
+        let mut by_key = titles.to_vec();
+        by_key.sort_by_cached_key(|t| ChapterSortKey::new(t));
+        assert_eq!(by_key, by_cmp);
+    }
+
+    #[test]
+    fn sorts_japanese_titles() {
+        let mut titles = vec!["第十話", "第２話", "第百話", "第十一話", "第一話", "第99話"];
+        titles.sort_by(|a, b| chapter_cmp(a, b));
+        assert_eq!(
+            titles,
+            ["第一話", "第２話", "第十話", "第十一話", "第99話", "第百話"]
+        );
+
+        let mut volumes = vec!["第2巻 (上)", "第1巻 下", "第2巻 (下)", "第1巻 上"];
+        volumes.sort_by(|a, b| chapter_cmp(a, b));
+        assert_eq!(
+            volumes,
+            ["第1巻 上", "第1巻 下", "第2巻 (上)", "第2巻 (下)"]
+        );
+    }
+}
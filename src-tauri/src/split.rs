@@ -0,0 +1,336 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+use image::{codecs::jpeg::JpegEncoder, DynamicImage};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
+use tauri::{command, AppHandle, Manager};
+
+use crate::{
+    database::{
+        data::v2::{MangaPanel, SplitPanel, SplitSpreads},
+        get_panels, get_split_panels, get_split_spreads, save_split_panels, save_split_spreads,
+        ReadingDirection,
+    },
+    error::{DatabaseError, MangaImageError},
+    fs::{decode_panel_image, read_panel_bytes},
+    layout::{measure_panels, SPREAD_ASPECT_RATIO},
+    misc::now_timestamp,
+    profile::resolve_reading_profile,
+};
+
+const SPLIT_JPEG_QUALITY: u8 = 90;
+
+pub fn splits_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("splits")
+}
+
+/// `app_data/splits/ab/abcdef....right.jpg`, keyed by the source path
+/// so a panel that changes is split into the same files again
+fn format_split_path(app_data_dir: &Path, source_path: &str, side: &str) -> PathBuf {
+    let hash = blake3::hash(source_path.as_bytes()).to_hex();
+    splits_dir(app_data_dir)
+        .join(&hash[..2])
+        .join(format!("{hash}.{side}.jpg"))
+}
+
+fn write_half(img: &DynamicImage, output: &Path) -> Result<(), MangaImageError> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let buf = BufWriter::new(File::create(output)?);
+    let encoder = JpegEncoder::new_with_quality(buf, SPLIT_JPEG_QUALITY);
+    DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)?;
+    Ok(())
+}
+
+/// cuts a wide panel down the middle and caches both halves
+fn split_panel(panel: &MangaPanel, app_data_dir: &Path) -> Result<SplitPanel, MangaImageError> {
+    let img = decode_panel_image(read_panel_bytes(&panel.path)?)?;
+    let half = img.width() / 2;
+
+    let left = format_split_path(app_data_dir, &panel.path, "left");
+    let right = format_split_path(app_data_dir, &panel.path, "right");
+    write_half(&img.crop_imm(0, 0, half, img.height()), &left)?;
+    write_half(
+        &img.crop_imm(half, 0, img.width() - half, img.height()),
+        &right,
+    )?;
+
+    Ok(SplitPanel {
+        source_path: panel.path.clone(),
+        parent_path: panel.parent_path.clone(),
+        source: panel.clone(),
+        left: left.to_string_lossy().to_string(),
+        right: right.to_string_lossy().to_string(),
+    })
+}
+
+/// a panel as the reader gets it, halves of a split panel point back to the panel they were cut from.
+/// the halves only live in the cache, so it's never stored.
+#[derive(Serialize, Clone, Debug)]
+pub struct ReaderPanel {
+    #[serde(flatten)]
+    pub panel: MangaPanel,
+    /// the panel a split half was cut from, only set on halves
+    pub source_path: Option<String>,
+}
+
+impl From<MangaPanel> for ReaderPanel {
+    fn from(panel: MangaPanel) -> Self {
+        ReaderPanel {
+            panel,
+            source_path: None,
+        }
+    }
+}
+
+/// a virtual panel for one half of a split panel, it shares the read state of its source
+fn half_panel(source: &MangaPanel, path: &str, side: &str) -> ReaderPanel {
+    ReaderPanel {
+        panel: MangaPanel {
+            path: path.to_string(),
+            title: format!("{} ({side})", source.title),
+            ..source.clone()
+        },
+        source_path: Some(source.path.clone()),
+    }
+}
+
+/// the halves of a split panel in the order they're read
+fn split_halves(panel: &MangaPanel, split: &SplitPanel, right_to_left: bool) -> [ReaderPanel; 2] {
+    let left = half_panel(panel, &split.left, "left");
+    let right = half_panel(panel, &split.right, "right");
+    if right_to_left {
+        [right, left]
+    } else {
+        [left, right]
+    }
+}
+
+/// a split is redone when its source changed on disk, or was scanned again since
+fn is_stale_split(split: &SplitPanel, panel: &MangaPanel) -> bool {
+    split.source.metadata != panel.metadata
+        || split.source.is_stale_metadata()
+        || !Path::new(&split.left).exists()
+        || !Path::new(&split.right).exists()
+}
+
+/// replaces the wide panels of a folder that has splitting turned on with their halves,
+/// in the folder's reading direction. panels that can't be split are kept whole.
+pub fn expand_split_panels(
+    handle: &AppHandle,
+    parent_path: &str,
+    panels: Vec<MangaPanel>,
+) -> Result<Vec<ReaderPanel>, DatabaseError> {
+    let Some(split_spreads) = get_split_spreads(handle, parent_path)?.filter(|s| s.is_enabled)
+    else {
+        return Ok(panels.into_iter().map(Into::into).collect());
+    };
+
+    let dimensions = measure_panels(handle, parent_path, &panels)?;
+    let is_wide = |panel: &MangaPanel| {
+        dimensions
+            .get(&panel.path)
+            .is_some_and(|d| d.aspect_ratio > SPREAD_ASPECT_RATIO)
+    };
+
+    let mut splits: HashMap<String, SplitPanel> = get_split_panels(handle, parent_path)?
+        .into_iter()
+        .map(|s| (s.source_path.clone(), s))
+        .collect();
+
+    let unsplit: Vec<&MangaPanel> = panels
+        .iter()
+        .filter(|p| is_wide(p))
+        .filter(|p| splits.get(&p.path).is_none_or(|s| is_stale_split(s, p)))
+        .collect();
+    if !unsplit.is_empty() {
+        let app_data_dir = handle.path().app_data_dir()?;
+        let new_splits: Vec<SplitPanel> = unsplit
+            .into_par_iter()
+            .filter_map(|panel| match split_panel(panel, &app_data_dir) {
+                Ok(split) => Some(split),
+                Err(e) => {
+                    eprintln!("failed to split {}: {e}", panel.path);
+                    None
+                }
+            })
+            .collect();
+
+        splits.extend(
+            new_splits
+                .iter()
+                .map(|s| (s.source_path.clone(), s.clone())),
+        );
+        save_split_panels(handle, new_splits)?;
+    }
+
+    let mut expanded = Vec::with_capacity(panels.len() + splits.len());
+    for panel in panels {
+        match splits.get(&panel.path).filter(|_| is_wide(&panel)) {
+            Some(split) => {
+                expanded.extend(split_halves(&panel, split, split_spreads.right_to_left))
+            }
+            None => expanded.push(panel.into()),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// the panels the reader shows: `get_panels`, with wide panels split if the folder asks for it
+pub fn reader_panels(
+    handle: &AppHandle,
+    parent_path: &str,
+) -> Result<Vec<MangaPanel>, DatabaseError> {
+    let panels = get_panels(handle.clone(), parent_path.to_string())?;
+    let panels = expand_split_panels(handle, parent_path, panels)?;
+    Ok(panels.into_iter().map(|p| p.panel).collect())
+}
+
+/// splitting decodes the wide panels, so it runs off the main thread
+#[command]
+pub async fn get_reader_panels(
+    handle: AppHandle,
+    parent_path: String,
+) -> Result<Vec<ReaderPanel>, DatabaseError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let panels = get_panels(handle.clone(), parent_path.clone())?;
+        expand_split_panels(&handle, &parent_path, panels)
+    })
+    .await?
+}
+
+#[command]
+pub fn get_folder_split_spreads(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<Option<SplitSpreads>, DatabaseError> {
+    get_split_spreads(&handle, &folder_path)
+}
+
+/// turns splitting of wide panels on or off for a folder.
+/// the halves follow the folder's reading direction unless `right_to_left` says otherwise.
+#[command]
+pub fn set_folder_split_spreads(
+    handle: AppHandle,
+    folder_path: String,
+    user_id: String,
+    is_enabled: bool,
+    right_to_left: Option<bool>,
+) -> Result<(), DatabaseError> {
+    let right_to_left = match right_to_left {
+        Some(right_to_left) => right_to_left,
+        None => {
+            resolve_reading_profile(&handle, &folder_path)?.direction
+                != ReadingDirection::LeftToRight
+        }
+    };
+    save_split_spreads(
+        &handle,
+        SplitSpreads {
+            folder_path,
+            user_id,
+            is_enabled,
+            right_to_left,
+            updated_at: now_timestamp(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::FileMetadata;
+
+    use super::*;
+
+    /// a wide panel and its halves, written to a temp dir of their own
+    fn split_fixture(name: &str) -> (PathBuf, MangaPanel, SplitPanel) {
+        let dir = std::env::temp_dir().join(format!("shelf-split-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let source = dir.join("001.jpg").to_string_lossy().to_string();
+        let left = dir.join("001.left.jpg").to_string_lossy().to_string();
+        let right = dir.join("001.right.jpg").to_string_lossy().to_string();
+        fs::write(&source, b"a wide page").unwrap();
+        fs::write(&left, b"left").unwrap();
+        fs::write(&right, b"right").unwrap();
+
+        let panel = MangaPanel {
+            user_id: "1".to_string(),
+            path: source.clone(),
+            title: "001.jpg".to_string(),
+            parent_path: dir.to_string_lossy().to_string(),
+            metadata: FileMetadata::from_path(&source),
+            is_read: true,
+            updated_at: 0,
+        };
+        let split = SplitPanel {
+            source_path: source,
+            parent_path: panel.parent_path.clone(),
+            source: panel.clone(),
+            left,
+            right,
+        };
+        (dir, panel, split)
+    }
+
+    #[test]
+    fn orders_halves_by_reading_direction() {
+        let (dir, panel, split) = split_fixture("order");
+
+        let [first, second] = split_halves(&panel, &split, true);
+        assert_eq!(first.panel.path, split.right);
+        assert_eq!(second.panel.path, split.left);
+        assert_eq!(first.panel.title, "001.jpg (right)");
+
+        let [first, second] = split_halves(&panel, &split, false);
+        assert_eq!(first.panel.path, split.left);
+        assert_eq!(second.panel.path, split.right);
+        assert_eq!(first.panel.title, "001.jpg (left)");
+
+        // both halves point back to the page progress is saved on
+        for half in [first, second] {
+            assert_eq!(half.source_path.as_deref(), Some(panel.path.as_str()));
+            assert!(half.panel.is_read);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fresh_splits_are_kept() {
+        let (dir, panel, split) = split_fixture("fresh");
+        assert!(!is_stale_split(&split, &panel));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn changed_sources_are_split_again() {
+        let (dir, _, split) = split_fixture("changed");
+
+        fs::write(&split.source_path, b"a different wide page").unwrap();
+        let rescanned = MangaPanel {
+            metadata: FileMetadata::from_path(&split.source_path),
+            ..split.source.clone()
+        };
+        assert!(is_stale_split(&split, &rescanned));
+        // the stored source is stale on its own too, before the folder is scanned again
+        assert!(is_stale_split(&split, &split.source));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_halves_are_split_again() {
+        let (dir, panel, split) = split_fixture("missing");
+        fs::remove_file(&split.right).unwrap();
+        assert!(is_stale_split(&split, &panel));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
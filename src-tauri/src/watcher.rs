@@ -0,0 +1,161 @@
+use std::{
+    path::PathBuf,
+    sync::{mpsc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use hashbrown::{HashMap, HashSet};
+use notify_debouncer_full::{
+    new_debouncer,
+    notify::{EventKind, RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer, RecommendedCache,
+};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    database::{
+        data::v2::OsFolder, find_nearest_os_folder, get_os_folder_children, get_root_os_folders,
+        get_user_by_id,
+    },
+    error::{MangaShelfError, WatcherError},
+    fs::upsert_read_os_dir,
+};
+
+/// how long a library root has to be quiet before a burst of changes gets applied
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// emitted with the paths of every folder that was re-read after a change on disk
+pub const LIBRARY_CHANGED_EVENT: &str = "library_changed";
+
+struct WatchedRoots {
+    debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+    roots: HashSet<String>,
+}
+
+/// watches every root `OsFolder` and keeps the database in sync with the disk
+pub struct LibraryWatcher(Mutex<WatchedRoots>);
+
+impl LibraryWatcher {
+    pub fn watch(&self, root: &str) -> Result<(), WatcherError> {
+        let mut watched = self.0.lock().unwrap();
+        if watched.roots.contains(root) {
+            return Ok(());
+        }
+
+        watched.debouncer.watch(root, RecursiveMode::Recursive)?;
+        watched.roots.insert(root.to_string());
+        Ok(())
+    }
+
+    pub fn unwatch(&self, root: &str) -> Result<(), WatcherError> {
+        let mut watched = self.0.lock().unwrap();
+        if watched.roots.remove(root) {
+            watched.debouncer.unwatch(root)?;
+        }
+        Ok(())
+    }
+}
+
+/// starts watching a newly added library root.
+/// the library still works without the watcher, so errors are only logged.
+pub fn watch_library_root(handle: &AppHandle, root: &str) {
+    if let Some(watcher) = handle.try_state::<LibraryWatcher>() {
+        if let Err(e) = watcher.watch(root) {
+            eprintln!("failed to watch {root}: {e}");
+        }
+    }
+}
+
+pub fn unwatch_library_root(handle: &AppHandle, root: &str) {
+    if let Some(watcher) = handle.try_state::<LibraryWatcher>() {
+        if let Err(e) = watcher.unwatch(root) {
+            eprintln!("failed to unwatch {root}: {e}");
+        }
+    }
+}
+
+pub fn init_watcher(handle: &AppHandle) -> Result<(), WatcherError> {
+    let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+    let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, None, tx)?;
+    let watcher = LibraryWatcher(Mutex::new(WatchedRoots {
+        debouncer,
+        roots: HashSet::new(),
+    }));
+
+    for folder in get_root_os_folders(handle)? {
+        if let Err(e) = watcher.watch(&folder.path) {
+            eprintln!("failed to watch {}: {e}", folder.path);
+        }
+    }
+    handle.manage(watcher);
+
+    // every batch is applied on this one thread, so syncs never race each other
+    let handle = handle.clone();
+    thread::spawn(move || {
+        for result in rx {
+            match result {
+                Ok(events) => {
+                    let paths: HashSet<PathBuf> = events
+                        .into_iter()
+                        .filter(|e| !matches!(e.kind, EventKind::Access(_)))
+                        .flat_map(|e| e.event.paths)
+                        .collect();
+                    if !paths.is_empty() {
+                        sync_changed_paths(&handle, paths);
+                    }
+                }
+                Err(errors) => errors.iter().for_each(|e| eprintln!("watcher error: {e}")),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// re-reads the closest library folder of every changed path
+/// and tells the frontend which folders were updated
+fn sync_changed_paths(handle: &AppHandle, paths: HashSet<PathBuf>) {
+    let mut folders: HashMap<String, OsFolder> = HashMap::new();
+    for path in paths {
+        match find_nearest_os_folder(handle, &path) {
+            Ok(Some(folder)) => {
+                folders.insert(folder.path.clone(), folder);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("failed to find the folder of {}: {e}", path.display()),
+        }
+    }
+
+    let mut changed = Vec::new();
+    for folder in folders.into_values() {
+        let path = folder.path.clone();
+        match tauri::async_runtime::block_on(sync_os_folder(handle.clone(), folder)) {
+            Ok(true) => changed.push(path),
+            Ok(false) => {}
+            Err(e) => eprintln!("failed to sync {path}: {e}"),
+        }
+    }
+
+    if !changed.is_empty() {
+        if let Err(e) = handle.emit(LIBRARY_CHANGED_EVENT, changed) {
+            eprintln!("{e}");
+        }
+    }
+}
+
+/// the same incremental upsert the frontend runs, with the old entries read from the database
+async fn sync_os_folder(handle: AppHandle, folder: OsFolder) -> Result<bool, MangaShelfError> {
+    let user = get_user_by_id(handle.clone(), folder.user_id.clone())?;
+    let (old_dirs, old_panels) = get_os_folder_children(&handle, &folder.path)?;
+
+    upsert_read_os_dir(
+        handle,
+        folder.path,
+        folder.parent_path,
+        user,
+        Some(old_dirs),
+        Some(old_panels),
+    )
+    .await
+}
@@ -0,0 +1,348 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+use image::{
+    codecs::jpeg::JpegEncoder,
+    imageops::{self, FilterType},
+    DynamicImage, RgbImage,
+};
+use tauri::{command, AppHandle, Manager};
+
+use crate::{
+    database::{
+        data::v2::{MangaPanel, PanelDimensions, WebtoonStrip},
+        get_panels, get_webtoon_strip, hash_panels, save_webtoon_strip, TileSource, WebtoonTile,
+    },
+    error::WebtoonError,
+    fs::{decode_panel_image, read_panel_bytes},
+    layout::measure_panels,
+};
+
+const TILE_JPEG_QUALITY: u8 = 90;
+
+/// tiles are this much taller than they are wide, about a screen of a webtoon
+const TILE_ASPECT_RATIO: f32 = 1.5;
+
+/// a last tile shorter than this part of a tile is joined onto the one before it
+const MIN_LAST_TILE: f32 = 0.25;
+
+pub fn webtoon_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("webtoon")
+}
+
+/// `app_data/webtoon/abcdef.../`, keyed by the folder path
+fn strip_dir(app_data_dir: &Path, folder_path: &str) -> PathBuf {
+    let hash = blake3::hash(folder_path.as_bytes()).to_hex();
+    webtoon_dir(app_data_dir).join(&hash[..32])
+}
+
+/// a panel in the strip, scaled to the width of the strip
+struct StripPanel {
+    path: String,
+    height: u32,
+    scaled_height: u32,
+    /// where the panel starts in the strip
+    top: u64,
+}
+
+impl StripPanel {
+    fn bottom(&self) -> u64 {
+        self.top + self.scaled_height as u64
+    }
+}
+
+/// the width most of the panels have, so they aren't all scaled
+fn strip_width(dimensions: &[&PanelDimensions]) -> Option<u32> {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    for d in dimensions {
+        *counts.entry(d.width).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(width, count)| (*count, *width))
+        .map(|(width, _)| width)
+}
+
+/// stacks the panels on top of each other at the same width.
+/// panels that couldn't be measured are left out.
+fn layout_strip(
+    panels: &[MangaPanel],
+    dimensions: &HashMap<String, PanelDimensions>,
+) -> Option<(u32, Vec<StripPanel>)> {
+    let measured: Vec<&PanelDimensions> = panels
+        .iter()
+        .filter_map(|p| dimensions.get(&p.path))
+        .filter(|d| d.width > 0 && d.height > 0)
+        .collect();
+    let width = strip_width(&measured)?;
+
+    let mut top = 0;
+    let strip = measured
+        .into_iter()
+        .map(|d| {
+            let scaled_height = ((d.height as u64 * width as u64) / d.width as u64).max(1) as u32;
+            let panel = StripPanel {
+                path: d.path.clone(),
+                height: d.height,
+                scaled_height,
+                top,
+            };
+            top += scaled_height as u64;
+            panel
+        })
+        .collect();
+
+    Some((width, strip))
+}
+
+/// cuts the strip into tiles of the same height, as `start..end` rows of the strip
+fn cut_tiles(length: u64, tile_height: u64) -> Vec<(u64, u64)> {
+    let mut tiles: Vec<(u64, u64)> = (0..length)
+        .step_by(tile_height as usize)
+        .map(|start| (start, (start + tile_height).min(length)))
+        .collect();
+
+    if tiles.len() > 1 {
+        let (start, end) = tiles[tiles.len() - 1];
+        if ((end - start) as f32) < tile_height as f32 * MIN_LAST_TILE {
+            tiles.pop();
+            tiles.last_mut().unwrap().1 = end;
+        }
+    }
+
+    tiles
+}
+
+/// the parts of the panels a tile is made of
+fn tile_sources(panels: &[StripPanel], start: u64, end: u64) -> Vec<TileSource> {
+    panels
+        .iter()
+        .filter(|p| p.top < end && p.bottom() > start)
+        .map(|p| {
+            let from = start.max(p.top);
+            let to = end.min(p.bottom());
+            // back from the strip's scale to the panel's own
+            let to_source = |rows: u64| (rows * p.height as u64 / p.scaled_height as u64) as u32;
+            TileSource {
+                path: p.path.clone(),
+                y: to_source(from - p.top),
+                height: to_source(to - from).max(1),
+                offset: (from - start) as u32,
+            }
+        })
+        .collect()
+}
+
+fn write_tile(img: &RgbImage, output: &Path) -> Result<(), WebtoonError> {
+    let buf = BufWriter::new(File::create(output)?);
+    let encoder = JpegEncoder::new_with_quality(buf, TILE_JPEG_QUALITY);
+    img.write_with_encoder(encoder)?;
+    Ok(())
+}
+
+/// decodes the panels one at a time and copies their rows into the tiles they show up in,
+/// so a panel that's 30,000px tall is only decoded once
+fn render_tiles(
+    panels: &[StripPanel],
+    width: u32,
+    dir: &Path,
+) -> Result<Vec<WebtoonTile>, WebtoonError> {
+    let length = panels.last().map_or(0, |p| p.bottom());
+    let tile_height = (width as f32 * TILE_ASPECT_RATIO) as u64;
+    let ranges = cut_tiles(length, tile_height.max(1));
+
+    let tiles: Vec<WebtoonTile> = ranges
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let sources = tile_sources(panels, start, end);
+            let height = (end - start) as u32;
+            // the rows a source takes up run to where the next one starts
+            let panel_path = sources
+                .iter()
+                .zip(sources.iter().skip(1).map(|s| s.offset).chain([height]))
+                .max_by_key(|(s, next)| next - s.offset)
+                .map(|(s, _)| s.path.clone())
+                .unwrap_or_default();
+            WebtoonTile {
+                path: dir
+                    .join(format!("{i:05}.jpg"))
+                    .to_string_lossy()
+                    .to_string(),
+                width,
+                height,
+                panel_path,
+                sources,
+            }
+        })
+        .collect();
+
+    let mut current = 0;
+    let mut buf = RgbImage::new(width, tiles[0].height);
+    for panel in panels {
+        let img = match read_panel_bytes(&panel.path).and_then(decode_panel_image) {
+            Ok(img) if img.width() == width && img.height() == panel.scaled_height => img,
+            Ok(img) => img.resize_exact(width, panel.scaled_height, FilterType::Triangle),
+            Err(e) => {
+                // the rows of a panel that can't be read are left black
+                eprintln!("failed to add {} to the webtoon strip: {e}", panel.path);
+                DynamicImage::new_rgb8(width, panel.scaled_height)
+            }
+        }
+        .to_rgb8();
+
+        while let Some(&(start, end)) = ranges.get(current) {
+            let from = start.max(panel.top);
+            let to = end.min(panel.bottom());
+            if to > from {
+                let rows = imageops::crop_imm(
+                    &img,
+                    0,
+                    (from - panel.top) as u32,
+                    width,
+                    (to - from) as u32,
+                );
+                imageops::replace(&mut buf, &*rows, 0, (from - start) as i64);
+            }
+
+            // the rest of the tile comes from the next panels
+            if end > panel.bottom() {
+                break;
+            }
+
+            write_tile(&buf, Path::new(&tiles[current].path))?;
+            current += 1;
+            if let Some(next) = tiles.get(current) {
+                buf = RgbImage::new(width, next.height);
+            }
+        }
+    }
+
+    Ok(tiles)
+}
+
+fn strip_fingerprint(panels: &[MangaPanel], width: u32) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&width.to_le_bytes());
+    hasher.update(&TILE_ASPECT_RATIO.to_le_bytes());
+    hash_panels(&mut hasher, panels);
+    hasher.finalize().to_hex().to_string()
+}
+
+/// the folder's panels as one long strip, cut into tiles of about a screen each.
+/// tall panels are cut up and short ones joined together, every tile says which panels it shows.
+/// the tiles are cut the first time and again once the folder's panels change.
+#[command]
+pub async fn get_webtoon_tiles(
+    handle: AppHandle,
+    folder_path: String,
+) -> Result<Vec<WebtoonTile>, WebtoonError> {
+    tauri::async_runtime::spawn_blocking(move || webtoon_tiles(&handle, folder_path)).await?
+}
+
+fn webtoon_tiles(
+    handle: &AppHandle,
+    folder_path: String,
+) -> Result<Vec<WebtoonTile>, WebtoonError> {
+    let panels = get_panels(handle.clone(), folder_path.clone())?;
+    let dimensions = measure_panels(handle, &folder_path, &panels)?;
+    let Some((width, strip)) = layout_strip(&panels, &dimensions) else {
+        return Ok(Vec::new());
+    };
+
+    let fingerprint = strip_fingerprint(&panels, width);
+    if let Some(cached) = get_webtoon_strip(handle, &folder_path)? {
+        if cached.fingerprint == fingerprint
+            && cached.tiles.iter().all(|t| Path::new(&t.path).exists())
+        {
+            return Ok(cached.tiles);
+        }
+    }
+
+    let dir = strip_dir(&handle.path().app_data_dir()?, &folder_path);
+    // the old tiles can be cut at other rows, so none of them are kept
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    let tiles = render_tiles(&strip, width, &dir)?;
+
+    save_webtoon_strip(
+        handle,
+        WebtoonStrip {
+            folder_path,
+            fingerprint,
+            dir: dir.to_string_lossy().to_string(),
+            tiles: tiles.clone(),
+        },
+    )?;
+
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_even_tiles() {
+        assert_eq!(cut_tiles(0, 400), vec![]);
+        assert_eq!(cut_tiles(300, 400), vec![(0, 300)]);
+        assert_eq!(cut_tiles(800, 400), vec![(0, 400), (400, 800)]);
+        assert_eq!(
+            cut_tiles(1000, 400),
+            vec![(0, 400), (400, 800), (800, 1000)]
+        );
+    }
+
+    #[test]
+    fn joins_a_short_last_tile() {
+        assert_eq!(cut_tiles(850, 400), vec![(0, 400), (400, 850)]);
+    }
+
+    fn source(path: &str, y: u32, height: u32, offset: u32) -> TileSource {
+        TileSource {
+            path: path.to_string(),
+            y,
+            height,
+            offset,
+        }
+    }
+
+    #[test]
+    fn maps_tiles_back_to_their_panels() {
+        // a panel that was scaled to half its height, followed by one at its own scale
+        let panels = [
+            StripPanel {
+                path: "a.jpg".to_string(),
+                height: 2000,
+                scaled_height: 1000,
+                top: 0,
+            },
+            StripPanel {
+                path: "b.jpg".to_string(),
+                height: 500,
+                scaled_height: 500,
+                top: 1000,
+            },
+        ];
+
+        assert_eq!(
+            tile_sources(&panels, 0, 400),
+            vec![source("a.jpg", 0, 800, 0)]
+        );
+        assert_eq!(
+            tile_sources(&panels, 800, 1200),
+            vec![source("a.jpg", 1600, 400, 0), source("b.jpg", 0, 200, 200)]
+        );
+        assert_eq!(
+            tile_sources(&panels, 1000, 1400),
+            vec![source("b.jpg", 0, 400, 0)]
+        );
+        assert_eq!(tile_sources(&panels, 1500, 1900), vec![]);
+    }
+}
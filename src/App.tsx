@@ -4,6 +4,7 @@ import { useNavigate } from "@solidjs/router";
 import get_default_user from "./tauri-cmds/users";
 import { UserType } from "./models";
 import { invoke } from "@tauri-apps/api/core";
+import { setClockFormat } from "./libs/datetime";
 
 function App() {
   const navigate = useNavigate();
@@ -11,6 +12,7 @@ function App() {
   onMount(async () => {
     let defaultUser = await get_default_user();
     if (defaultUser) {
+      setClockFormat(defaultUser.settings.clock);
       navigate("/dashboard");
     } else {
       const dbUser: UserType = {
@@ -20,8 +22,11 @@ function App() {
         settings: {
           user_id: "1",
           autoplay: true,
-          update_date: "",
-          update_time: "",
+          clock: "Locale",
+          auto_crop: false,
+          crop_tolerance: 24,
+          reading_profile: { direction: "RightToLeft", fit_mode: "Screen", page_gap: 0, offset_cover: false },
+          updated_at: 0,
         }
       };
       invoke("update_user", { user: dbUser }).then(() => {
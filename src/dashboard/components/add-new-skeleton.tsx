@@ -1,11 +1,13 @@
-import { IconFilePlus, IconFolderPlus, IconPlus } from "@tabler/icons-solidjs";
+import { IconFilePlus, IconFolderPlus, IconPlus, IconX } from "@tabler/icons-solidjs";
 import { Popover, PopoverContent, PopoverTrigger } from "../../components/ui/popover";
 import { open } from '@tauri-apps/plugin-dialog';
-import { OsFolder, UserType } from "../../models";
-import { Accessor, createSignal, Show } from "solid-js";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { OsFolder, ScanProgress, SkippedFolder, UserType } from "../../models";
+import { Accessor, createSignal, onCleanup, onMount, Show } from "solid-js";
 import { Transition } from "solid-transition-group";
 import upsert_read_os_dir from "../../tauri-cmds/handle_stale_folder";
 import Spinner from "../../main-components/icons/spinner";
+import cancel_scan from "../../tauri-cmds/cancel_scan";
 
 const AddNewSkeleton = ({
   user,
@@ -17,6 +19,45 @@ const AddNewSkeleton = ({
 ) => {
 
   const [isLoading, setIsLoading] = createSignal(false);
+  const [scanRoot, setScanRoot] = createSignal<string | null>(null);
+  const [progress, setProgress] = createSignal<ScanProgress | null>(null);
+  const [skipped, setSkipped] = createSignal<SkippedFolder[]>([]);
+
+  // the backend reports how far along the scan of a newly added folder is
+  let unlisten: UnlistenFn | null;
+  // and which folders it had to leave out
+  let unlistenSkipped: UnlistenFn | null;
+
+  onMount(async () => {
+    unlisten = await listen<ScanProgress>("scan_progress", (event) => {
+      if (event.payload.root === scanRoot()) {
+        setProgress(event.payload);
+      }
+    });
+    unlistenSkipped = await listen<SkippedFolder>("scan_skipped", (event) => {
+      if (event.payload.root === scanRoot()) {
+        setSkipped((skipped) => [...skipped, event.payload]);
+      }
+    });
+  });
+
+  onCleanup(() => {
+    if (unlisten) {
+      unlisten();
+    }
+    if (unlistenSkipped) {
+      unlistenSkipped();
+    }
+  });
+
+  const handleCancel = async (e: MouseEvent) => {
+    // the card is the popover's trigger, so it shouldn't open the popover as well
+    e.stopPropagation();
+    const root = scanRoot();
+    if (root) {
+      await cancel_scan(root);
+    }
+  };
 
   return (
     <Transition
@@ -73,6 +114,30 @@ const AddNewSkeleton = ({
 									top-0 bottom-0 left-0 right-0 m-auto"
               />
             </Show>
+            <Show when={isLoading() && progress()}>
+              {(progress) => (
+                <div class="absolute bottom-2 left-0 right-0 flex flex-col items-center gap-1 px-2 text-xs text-zinc-500">
+                  <span class="text-center">
+                    {progress().folders_discovered} folders, {progress().panels_indexed} pages
+                  </span>
+                  <Show when={progress().stage === "compressing_covers"}>
+                    <span class="text-center">{progress().covers_compressed} covers</span>
+                  </Show>
+                  <Show when={skipped().length > 0}>
+                    <span class="text-center" title={skipped().map((folder) => `${folder.path}: ${folder.reason}`).join("\n")}>
+                      {skipped().length} skipped
+                    </span>
+                  </Show>
+                  <span
+                    class="flex flex-row items-center gap-0.5 rounded-sm bg-zinc-100 px-1.5 py-0.5 font-medium hover:opacity-50 transition-opacity duration-100 ease-in-out"
+                    onClick={handleCancel}
+                  >
+                    <IconX class="w-3 h-3" />
+                    Cancel
+                  </span>
+                </div>
+              )}
+            </Show>
           </div>
         </PopoverTrigger>
         <PopoverContent class="
@@ -84,10 +149,14 @@ const AddNewSkeleton = ({
                 setIsLoading(true);
                 let dir_path = await open({ directory: true });
                 if (dir_path) {
+                  setSkipped([]);
+                  setScanRoot(dir_path);
                   await upsert_read_os_dir(dir_path, undefined, user()!, undefined, undefined);
                   refetch();
                 }
                 setIsLoading(false);
+                setScanRoot(null);
+                setProgress(null);
               }}
             >
               <IconFolderPlus
@@ -1,6 +1,5 @@
 import { OsFolder, UserType } from "../../models";
 import { Accessor, Show } from "solid-js";
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { ContextMenu, ContextMenuTrigger } from "../../components/ui/context-menu";
 import { platform } from "@tauri-apps/plugin-os";
 import FolderCardContextMenuContent from "./folder-card-cm-context";
@@ -8,6 +7,8 @@ import { useNavigate } from "@solidjs/router";
 import { Transition } from "solid-transition-group";
 import { cn } from "../../libs/cn";
 import { OsFolderDescription } from "../../main-components/description/folder-desc";
+import { createCoverSrc } from "../../libs/covers";
+import { IconPlugConnectedX } from "@tabler/icons-solidjs";
 
 const OsFolderCard = ({
   folder,
@@ -23,6 +24,8 @@ const OsFolderCard = ({
   const currentPlatform = platform();
   const encodedFolderPath = encodeURIComponent(folder.path);
   const navigate = useNavigate();
+  // the card is at most w-56 wide
+  const coverSrc = createCoverSrc(() => folder.cover_img_path, 224);
 
   return (
     <Transition
@@ -46,7 +49,8 @@ const OsFolderCard = ({
             class={cn(`group w-fit flex items-center 
 						transition-all duration-200 ease-in-out
 						cursor-pointer select-none will-change-auto`,
-              folder.is_hidden && "brightness-75"
+              folder.is_hidden && "brightness-75",
+              folder.is_offline && "grayscale cursor-not-allowed"
             )}
           >
             <div
@@ -59,6 +63,10 @@ const OsFolderCard = ({
 									rounded-sm shadow-md bg-white 
 									flex items-center justify-center overflow-hidden relative will-change-transfor"
               onClick={() => {
+                // there's nothing to read until the drive is plugged back in
+                if (folder.is_offline) {
+                  return;
+                }
                 if (folder.is_manga_folder) {
                   navigate(`/reader/${encodedFolderPath}`);
                 } else {
@@ -69,7 +77,7 @@ const OsFolderCard = ({
               <div class="h-full w-full folder-card-container relative inset-0 z-10">
                 <Show when={folder.cover_img_path}>
                   <img
-                    src={convertFileSrc(folder.cover_img_path!)}
+                    src={coverSrc()}
                     class={cn("object-fill w-full h-full relative z-10 bg-black will-change-transform",
                       folder.is_hidden && "blur-[10px]"
                     )}
@@ -84,6 +92,12 @@ const OsFolderCard = ({
                   {folder.title}
                 </span>
               </Show>
+              <Show when={folder.is_offline}>
+                <span class="absolute top-1 right-1 z-20 flex flex-row items-center gap-0.5 bg-primary/80 text-secondary text-xs font-medium rounded-sm px-1 py-0.5 select-none">
+                  <IconPlugConnectedX class="h-3 w-3" />
+                  Offline
+                </span>
+              </Show>
               <OsFolderDescription
                 folder={() => folder}
               />
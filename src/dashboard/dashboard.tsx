@@ -1,4 +1,5 @@
-import { createResource, createSignal, For, Show } from "solid-js";
+import { createResource, createSignal, For, onCleanup, onMount, Show } from "solid-js";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import NavBar from "../main-components/navbar";
 import { OsFolder, UserType } from "../models";
 import AddNewSkeleton from "./components/add-new-skeleton";
@@ -21,6 +22,21 @@ export default function Dashboard() {
   });
   const [showHiddenFolders, setShowHiddenFolders] = createSignal(false);
 
+  // the backend watches every library folder and emits this after syncing changes from disk
+  let unlisten: UnlistenFn | null;
+
+  onMount(async () => {
+    unlisten = await listen<string[]>("library_changed", () => {
+      refetch();
+    });
+  });
+
+  onCleanup(() => {
+    if (unlisten) {
+      unlisten();
+    }
+  });
+
   return (
     <main>
       <NavBar showHiddenFolders={showHiddenFolders} setShowHiddenFolders={setShowHiddenFolders} />
@@ -0,0 +1,20 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { Accessor, createResource } from "solid-js";
+import { get_cover_variants, pick_cover_variant } from "../tauri-cmds/thumbnails/get_cover_variants";
+
+// the src of the smallest thumbnail of a cover that fills `cssWidth`,
+// the cover itself is shown until its thumbnails are loaded or if it has none
+export function createCoverSrc(coverPath: Accessor<string | null | undefined>, cssWidth: number) {
+  const [variants] = createResource(coverPath, (path) => get_cover_variants([path]));
+
+  return () => {
+    const path = coverPath();
+    if (!path) {
+      return undefined;
+    }
+    const thumbnails = variants()?.[0]?.variants;
+    return convertFileSrc(
+      thumbnails?.length ? pick_cover_variant(thumbnails, cssWidth * window.devicePixelRatio).path : path,
+    );
+  };
+}
@@ -0,0 +1,29 @@
+import { createSignal } from "solid-js";
+import { ClockFormat } from "../models";
+
+// the user's clock setting, set once the user is loaded so every timestamp follows it
+const [clockFormat, setClockFormat] = createSignal<ClockFormat>("Locale");
+
+export { clockFormat, setClockFormat };
+
+const hour12 = (clock: ClockFormat) => {
+  switch (clock) {
+    case "TwelveHour":
+      return true;
+    case "TwentyFourHour":
+      return false;
+    default:
+      return undefined;
+  }
+};
+
+// timestamps are stored in UTC, they're shown in the local time zone
+export const formatDate = (timestamp: number) =>
+  new Date(timestamp).toLocaleDateString(undefined, { dateStyle: "medium" });
+
+export const formatTime = (timestamp: number) =>
+  new Date(timestamp).toLocaleTimeString(undefined, {
+    hour: "numeric",
+    minute: "2-digit",
+    hour12: hour12(clockFormat()),
+  });
@@ -0,0 +1,24 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { Margins } from "../models";
+
+// widths are rounded up to a step so resizing the window doesn't miss the page cache every pixel
+const WIDTH_STEP = 200;
+
+export const pageWidthFor = (cssWidth: number) =>
+  Math.ceil((cssWidth * window.devicePixelRatio) / WIDTH_STEP) * WIDTH_STEP;
+
+// the width pages are requested at in the reader, prefetching has to use the same one to hit the cache
+export const readerPageWidth = (isDoublePanels: boolean) =>
+  pageWidthFor(isDoublePanels ? window.innerWidth / 2 : window.innerWidth);
+
+// a page served by the `shelf://` protocol, cropped and downscaled to `width` if it's any wider
+export const shelfPageSrc = (path: string, width?: number, crop?: Margins | null) => {
+  const params = new URLSearchParams({ path });
+  if (width) {
+    params.set("w", String(width));
+  }
+  if (crop) {
+    params.set("crop", [crop.top, crop.right, crop.bottom, crop.left].join(","));
+  }
+  return `${convertFileSrc("page", "shelf")}?${params.toString()}`;
+};
@@ -3,6 +3,7 @@ import { IconBookFilled } from "@tabler/icons-solidjs";
 import { OsFolder } from "../../models";
 import { bytesToMB, getTitlesFromPath, splitTitleDots } from "./desc-util";
 import IconHeroSlashEye from "../icons/icon-hero-slash-eye";
+import { formatDate, formatTime } from "../../libs/datetime";
 
 export const OsFolderDescription: Component<{ folder: () => OsFolder }> = (props) => {
 
@@ -39,6 +40,11 @@ export const OsFolderDescription: Component<{ folder: () => OsFolder }> = (props
                 {props.folder().metadata?.contains.folders} dirs
               </p>
             </Show>
+            <Show when={props.folder().metadata?.page_count != null}>
+              <p class="text-[13px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5">
+                {props.folder().metadata?.page_count} pages
+              </p>
+            </Show>
             <p class="text-[13px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5">
               {bytesToMB(props.folder().metadata?.size)}
             </p>
@@ -46,10 +52,10 @@ export const OsFolderDescription: Component<{ folder: () => OsFolder }> = (props
         </div>
         <div class="absolute bottom-0 right-0 flex flex-col items-end m-2">
           <p class="text-[12px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5 leading-tight">
-            {props.folder().update_date}
+            {formatDate(props.folder().updated_at)}
           </p>
           <p class="text-[13px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5 leading-tight">
-            {props.folder().update_time}
+            {formatTime(props.folder().updated_at)}
           </p>
         </div>
       </div>
@@ -1,6 +1,7 @@
 import { Component } from "solid-js";
 import { MangaPanel } from "../../models";
 import { bytesToMB, getTitlesFromPath, splitTitleDots } from "./desc-util";
+import { formatDate, formatTime } from "../../libs/datetime";
 
 export const MangaPanelDescription: Component<{ panel: () => MangaPanel }> = (props) => {
 
@@ -22,10 +23,10 @@ export const MangaPanelDescription: Component<{ panel: () => MangaPanel }> = (pr
         </div>
         <div class="absolute bottom-0 right-0 flex flex-col items-end m-2">
           <p class="text-[12px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5 leading-tight">
-            {props.panel().update_date}
+            {formatDate(props.panel().updated_at)}
           </p>
           <p class="text-[13px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5 leading-tight">
-            {props.panel().update_time}
+            {formatTime(props.panel().updated_at)}
           </p>
         </div>
         <p class="text-[13px] font-medium text-zinc-300 bg-transparent mix-blend-difference w-fit z-10 shadow-2xl rounded-none px-0.5 mt-2">
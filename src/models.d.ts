@@ -10,10 +10,19 @@ export type SettingsType = {
   mpv_path?: string;
   plugins_path?: string;
   autoplay: boolean;
-  update_date: string;
-  update_time: string;
+  clock: ClockFormat;
+  // crops page margins by default, folders can override it
+  auto_crop: boolean;
+  // how far a pixel can be from the margin's color and still count as margin
+  crop_tolerance: number;
+  // the reading profile folders fall back to when neither they nor their parents set it
+  reading_profile: ReadingProfile;
+  // milliseconds since the unix epoch in UTC
+  updated_at: number;
 }
 
+export type ClockFormat = "Locale" | "TwelveHour" | "TwentyFourHour";
+
 export type UserFormType = {
   username: string;
 }
@@ -31,8 +40,10 @@ export type OsFolder = {
   is_read: boolean;
   zoom: number;
   is_hidden: boolean;
-  update_date: string;
-  update_time: string;
+  updated_at: number;
+  // the folder's library root is on a drive that isn't plugged in, its progress is kept until it's back.
+  // only set on the folders the library listings return, it isn't stored with the folder
+  is_offline?: boolean;
 }
 
 export type MangaPanel = {
@@ -42,8 +53,12 @@ export type MangaPanel = {
   parent_path: string;
   metadata: FileMetadata;
   is_read: bool;
-  update_date: string;
-  update_time: string;
+  updated_at: number;
+}
+
+// a panel as the reader gets it, halves of a split panel point back to the panel they were cut from
+export type ReaderPanel = MangaPanel & {
+  source_path: string | null;
 }
 
 export type FileMetadata = {
@@ -56,6 +71,8 @@ export type FileMetadata = {
 export type FolderMetadata = {
   contains: FolderContains;
   size: number;
+  // only set for pdfs
+  page_count: number | null;
 }
 
 export type FolderContains = {
@@ -63,4 +80,150 @@ export type FolderContains = {
   folders: number;
 }
 
-export type SortType = "none" | "alphabet" | "numbers" | "updated";
+export type SortType = "none" | "alphabet" | "numbers" | "updated" | "natural";
+
+export type ScanStage = "reading" | "compressing_covers" | "saving" | "finished" | "cancelled";
+
+export type ScanProgress = {
+  root: string;
+  stage: ScanStage;
+  folders_discovered: number;
+  panels_indexed: number;
+  covers_compressed: number;
+  current_path: string;
+}
+
+// a folder or archive the scan had to leave out, like an archive with an unsupported compression method
+export type SkippedFolder = {
+  root: string;
+  path: string;
+  reason: string;
+}
+
+export type LibraryRoot = {
+  path: string;
+  user_id: string;
+  is_offline: boolean;
+  updated_at: number;
+}
+
+export type ThumbnailSize = "Grid" | "Detail" | "Full";
+export type ThumbnailFormat = "Jpeg" | "Webp";
+export type ThumbnailQuality = "Low" | "Balanced" | "High";
+
+export type ThumbnailSettings = {
+  user_id: string;
+  format: ThumbnailFormat;
+  quality: ThumbnailQuality;
+  sizes: ThumbnailSize[];
+  updated_at: number;
+}
+
+export type CoverVariant = {
+  size: ThumbnailSize;
+  path: string;
+  width: number;
+  height: number;
+}
+
+export type CoverVariants = {
+  cover_path: string;
+  variants: CoverVariant[];
+}
+
+export type PanelDimensions = {
+  path: string;
+  parent_path: string;
+  width: number;
+  height: number;
+  aspect_ratio: number;
+}
+
+// indexes into the folder's panels, `first` is the right page for right-to-left reading
+export type Spread = {
+  first: number;
+  second: number | null;
+  is_wide: boolean;
+}
+
+export type SplitSpreads = {
+  folder_path: string;
+  user_id: string;
+  is_enabled: boolean;
+  right_to_left: boolean;
+  updated_at: number;
+}
+
+// fractions of the page's width and height
+export type Margins = {
+  top: number;
+  right: number;
+  bottom: number;
+  left: number;
+}
+
+export type FolderCrop = {
+  folder_path: string;
+  user_id: string;
+  is_enabled: boolean;
+  updated_at: number;
+}
+
+export type ReadingDirection = "RightToLeft" | "LeftToRight" | "Vertical";
+
+export type FitMode = "Screen" | "Width" | "Height" | "Original";
+
+export type ReadingProfile = {
+  direction: ReadingDirection;
+  fit_mode: FitMode;
+  page_gap: number;
+  offset_cover: boolean;
+}
+
+// `null` is inherited from the parent folders, then the user's default
+export type ReadingProfileOverrides = {
+  direction: ReadingDirection | null;
+  fit_mode: FitMode | null;
+  page_gap: number | null;
+  offset_cover: boolean | null;
+}
+
+export type FolderReadingProfile = {
+  folder_path: string;
+  user_id: string;
+  profile: ReadingProfileOverrides;
+  updated_at: number;
+}
+
+// rows of a panel in a webtoon tile, `y` and `height` are in the panel's pixels
+export type TileSource = {
+  path: string;
+  y: number;
+  height: number;
+  offset: number;
+}
+
+export type WebtoonTile = {
+  path: string;
+  width: number;
+  height: number;
+  // the panel read progress is saved to
+  panel_path: string;
+  sources: TileSource[];
+}
+
+// a copy of the whole database, `created_at` is in UTC milliseconds
+export type DatabaseSnapshot = {
+  path: string;
+  created_at: number;
+  size: number;
+}
+
+// merge keeps whichever record was updated last, replace deletes every user, folder and panel first
+export type ImportStrategy = "Merge" | "Replace";
+
+export type ImportSummary = {
+  users: number;
+  folders: number;
+  panels: number;
+}
@@ -33,8 +33,11 @@ export default function CreateProfile() {
       settings: {
         user_id: "1",
         autoplay: true,
-        update_date: "",
-        update_time: "",
+        clock: "Locale",
+        auto_crop: false,
+        crop_tolerance: 24,
+        reading_profile: { direction: "RightToLeft", fit_mode: "Screen", page_gap: 0, offset_cover: false },
+        updated_at: 0,
       }
     };
     invoke("update_user", { user: dbUser }).then(() => {
@@ -1,14 +1,14 @@
 import { OsFolder, UserType } from "../../models";
 import { Accessor, Resource, Show } from "solid-js";
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { ContextMenu, ContextMenuTrigger } from "../../components/ui/context-menu";
 import { Transition } from "solid-transition-group";
 import FolderCardContextMenuContent from "../../dashboard/components/folder-card-cm-context";
 import { platform } from "@tauri-apps/plugin-os";
 import { cn } from "../../libs/cn";
 import IconHeroSlashEye from "../../main-components/icons/icon-hero-slash-eye";
-import { IconBookFilled } from "@tabler/icons-solidjs";
+import { IconBookFilled, IconPlugConnectedX } from "@tabler/icons-solidjs";
 import { OsFolderDescription } from "../../main-components/description/folder-desc";
+import { createCoverSrc } from "../../libs/covers";
 
 const LibraryFolderCard = ({
   user,
@@ -26,6 +26,8 @@ const LibraryFolderCard = ({
   onClick: (event: MouseEvent) => void;
 }) => {
   const currentPlatform = platform();
+  // the grid cells are about as wide as the dashboard cards
+  const coverSrc = createCoverSrc(() => folder.cover_img_path, 224);
   return (
     <Transition
       appear={true}
@@ -45,6 +47,7 @@ const LibraryFolderCard = ({
               `w-full h-48 lg:h-56 min-h-30 cursor-pointer relative border-[1.5px]
 						border-transparent rounded-sm shadow-black/30 shadow-md flex items-center
 						justify-center overflow-hidden will-change-auto transition-all group`,
+              folder.is_offline && "grayscale",
             )}
             onClick={onClick}
           >
@@ -52,7 +55,7 @@ const LibraryFolderCard = ({
             {/* Folder Image */}
             <Show when={folder.cover_img_path}>
               <img
-                src={folder.cover_img_path && convertFileSrc(folder.cover_img_path)}
+                src={coverSrc()}
                 class={cn("object-cover w-full h-full relative select-none object-top",
                   (folder.is_read || folder.is_hidden) ? "brightness-[0.7]" : ""
                 )}
@@ -75,6 +78,12 @@ const LibraryFolderCard = ({
 									group-hover:opacity-0 transition-all duration-300 fill-secondary"
               />
             </Show>
+            <Show when={folder.is_offline}>
+              <span class="absolute top-1 right-1 z-20 flex flex-row items-center gap-0.5 bg-primary/80 text-secondary text-xs font-medium rounded-sm px-1 py-0.5 select-none">
+                <IconPlugConnectedX class="h-3 w-3" />
+                Offline
+              </span>
+            </Show>
             <Show when={folder.is_read && !folder.is_hidden}>
               <IconBookFilled
                 class="h-[40%] w-fit bg-primary rounded-md absolute p-1 opacity-60
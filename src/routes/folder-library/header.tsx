@@ -1,10 +1,12 @@
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { Resource, Show } from "solid-js";
 import { OsFolder, UserType } from "../../models";
 import { useNavigate } from "@solidjs/router";
 import { IconBookFilled } from "@tabler/icons-solidjs";
 import { cn } from "../../libs/cn";
 import { MangaPanelDescription } from "../../main-components/description/panel-desc";
+import { formatDate, formatTime } from "../../libs/datetime";
+import { createCoverSrc } from "../../libs/covers";
+import { pageWidthFor, shelfPageSrc } from "../../libs/shelf";
 
 export function escapeCSSUrl(url: string) {
   return url.replace(/\(/g, "\\(").replace(/\)/g, "\\)");
@@ -20,6 +22,8 @@ export default function ({
   lastReadMangaFolder: Resource<OsFolder | null>;
 }) {
   const navigate = useNavigate();
+  // the cover is at most h-[400px] tall, the blurred background gets by with the same thumbnail
+  const coverSrc = createCoverSrc(() => mainParentFolder()?.cover_img_path, 320);
 
   return (
     <header class="sm:px-2 md:px-16 lg:px-30 xl:px-40 w-full h-fit py-3 px-2 relative will-change">
@@ -30,8 +34,8 @@ export default function ({
             "background-image": `linear-gradient(rgba(0,0,0,.2),rgba(0,0,0,.2)),
 						url(${
               mainParentFolder()?.last_read_panel
-                ? escapeCSSUrl(convertFileSrc(mainParentFolder()?.last_read_panel?.path!))
-                : escapeCSSUrl(convertFileSrc(mainParentFolder()?.cover_img_path!))
+                ? escapeCSSUrl(shelfPageSrc(mainParentFolder()?.last_read_panel?.path!, pageWidthFor(320)))
+                : escapeCSSUrl(coverSrc()!)
             })`,
             "background-size": "cover",
             "background-repeat": "no-repeat",
@@ -54,7 +58,7 @@ export default function ({
 					bg-transparent mix-blend-luminosity rounded-[2px]  border-secondary/50 border-[1.5px] shadow-md
 					backdrop-blur-lg select-none cursor-default"
         >
-          {mainParentFolder()?.update_date}
+          <Show when={mainParentFolder()}>{(folder) => formatDate(folder().updated_at)}</Show>
         </h2>
         <h3
           class="text-secondary mb-2 w-fit font-semibold z-15 relative
@@ -62,7 +66,7 @@ export default function ({
 					bg-transparent mix-blend-luminosity rounded-[2px] border-secondary/50 border-[1.5px] shadow-md
 					backdrop-blur-lg select-none cursor-default"
         >
-          {mainParentFolder()?.update_time}
+          <Show when={mainParentFolder()}>{(folder) => formatTime(folder().updated_at)}</Show>
         </h3>
       </div>
       <Show when={mainParentFolder()?.title && mainParentFolder()?.cover_img_path}>
@@ -80,8 +84,8 @@ export default function ({
               onError={() => {}}
               src={
                 mainParentFolder()?.last_read_panel?.path
-                  ? convertFileSrc(mainParentFolder()?.last_read_panel?.path!)
-                  : convertFileSrc(mainParentFolder()?.cover_img_path!)
+                  ? shelfPageSrc(mainParentFolder()?.last_read_panel?.path!, pageWidthFor(320))
+                  : coverSrc()
               }
               class="select-none h-72 md:h-[320px] object-contain lg:h-[400px]
               w-auto z-30 bg-black
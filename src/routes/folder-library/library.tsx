@@ -1,7 +1,8 @@
 import { useParams } from "@solidjs/router";
 import { Transition } from "solid-transition-group";
 import LibraryHeader from "./header";
-import { createEffect, createResource, createSignal, Show } from "solid-js";
+import { createEffect, createResource, createSignal, onCleanup, onMount, Show } from "solid-js";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import NavBar from "../../main-components/navbar";
 import get_user_by_id from "../../tauri-cmds/get_user_by_id";
 import get_os_folder_by_path from "../../tauri-cmds/mpv/get_os_folder_by_path";
@@ -15,7 +16,7 @@ export default function Library() {
   const params = useParams();
   const folderPath = () => decodeURIComponent(params.folder || "");
 
-  const [mainParentFolder] = createResource(() => folderPath(), get_os_folder_by_path);
+  const [mainParentFolder, { refetch: refetchMainParentFolder }] = createResource(() => folderPath(), get_os_folder_by_path);
   const [user] = createResource(() => (mainParentFolder() ? mainParentFolder()?.user_id : null), get_user_by_id);
   const [childFolders, { refetch: refetchChildFolders }] = createResource(
     () => (mainParentFolder() ? mainParentFolder()?.path : null),
@@ -70,6 +71,24 @@ export default function Library() {
     }
   });
 
+  // the backend emits the folders it re-read after a change on disk, only this folder and the ones below it matter here
+  let unlisten: UnlistenFn | null;
+
+  onMount(async () => {
+    unlisten = await listen<string[]>("library_changed", (event) => {
+      if (event.payload.some((path) => path.startsWith(folderPath()))) {
+        refetchMainParentFolder();
+        refetchChildFolders();
+      }
+    });
+  });
+
+  onCleanup(() => {
+    if (unlisten) {
+      unlisten();
+    }
+  });
+
   return (
     <main class="w-full h-[100vh] relative overflow-auto" style={{ "scrollbar-gutter": "stable" }}>
       <NavBar showHiddenFolders={showHiddenChildFolders} setShowHiddenFolders={setShowHiddenChildFolders} />
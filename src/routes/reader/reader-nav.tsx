@@ -1,8 +1,9 @@
-import { IconAdjustments, IconArrowNarrowLeftDashed, IconColumns1, IconColumns2, IconZoomIn, IconZoomOut } from "@tabler/icons-solidjs";
+import { IconAdjustments, IconArrowNarrowLeftDashed, IconArrowsVertical, IconColumns1, IconColumns2, IconCrop, IconSeparatorVertical, IconTextDirectionLtr, IconTextDirectionRtl, IconZoomIn, IconZoomOut } from "@tabler/icons-solidjs";
 import { A, useNavigate } from "@solidjs/router";
 import { Accessor, createSignal, onCleanup, onMount, Resource, Setter, Show } from "solid-js";
-import { MangaPanel, OsFolder, UserType } from "../../models";
+import { MangaPanel, OsFolder, ReadingDirection, UserType } from "../../models";
 import update_os_folders from "../../tauri-cmds/os_folders/update_os_folders";
+import { cn } from "../../libs/cn";
 
 export default function ReaderNavbar({
   user,
@@ -14,6 +15,12 @@ export default function ReaderNavbar({
   setPanelIndex,
   setCurrentMangaFolder,
   handleSetDoublePanels,
+  isSplitSpreads,
+  handleSetSplitSpreads,
+  isAutoCrop,
+  handleSetAutoCrop,
+  direction,
+  handleSetDirection,
   handleSetFirstPanel,
   handleSetLastPanel,
   handlePrevSinglePanel,
@@ -30,6 +37,12 @@ export default function ReaderNavbar({
   setPanelIndex: Setter<number>,
   setCurrentMangaFolder: Setter<OsFolder | undefined>
   handleSetDoublePanels(): Promise<void>;
+  isSplitSpreads: Accessor<boolean>;
+  handleSetSplitSpreads(): Promise<void>;
+  isAutoCrop: Accessor<boolean>;
+  handleSetAutoCrop(): Promise<void>;
+  direction: Accessor<ReadingDirection>;
+  handleSetDirection(): Promise<void>;
   handleSetFirstPanel: () => Promise<void>;
   handleSetLastPanel: () => Promise<void>;
   handlePrevSinglePanel: () => Promise<void>;
@@ -99,16 +112,22 @@ export default function ReaderNavbar({
   const handleKeyDown = async (
     event: KeyboardEvent,
   ) => {
+    // the arrow pointing at the next page depends on the reading direction
+    const [back, forward] = direction() === "LeftToRight" ? ["ArrowLeft", "ArrowRight"] : ["ArrowRight", "ArrowLeft"];
+    if (direction() === "Vertical" && event.key.startsWith("Arrow")) {
+      return;
+    }
+
     // set first and last panel
-    if (event.ctrlKey && event.key === "ArrowRight") {
+    if (event.ctrlKey && event.key === back) {
       await handleSetFirstPanel();
-    } else if (event.ctrlKey && event.key === "ArrowLeft") {
+    } else if (event.ctrlKey && event.key === forward) {
       await handleSetLastPanel();
     }
     // previous panels
-    else if (event.shiftKey && event.key === "ArrowRight") {
+    else if (event.shiftKey && event.key === back) {
       await handlePrevSinglePanel();
-    } else if (event.key === "ArrowRight") {
+    } else if (event.key === back) {
       if (isDoublePanels()) {
         await handlePrevPanel();
       } else {
@@ -116,9 +135,9 @@ export default function ReaderNavbar({
       }
     }
     // next panels
-    else if (event.shiftKey && event.key === "ArrowLeft") {
+    else if (event.shiftKey && event.key === forward) {
       await handleNextSinglePanel();
-    } else if (event.key === "ArrowLeft") {
+    } else if (event.key === forward) {
       if (isDoublePanels()) {
         await handleNextPanel();
       } else {
@@ -181,6 +200,45 @@ export default function ReaderNavbar({
               <IconColumns1 class="text-secondary fill-accent stroke-[1.5]" />
             </Show>
           </li>
+          <li
+            class={cn(
+              "px-1 h-full flex flex-row justify-center items-center hover:bg-accent transition-colors cursor-pointer",
+              isSplitSpreads() && "bg-accent",
+            )}
+            title="split double page scans"
+            onClick={async () => await handleSetSplitSpreads()}
+          >
+            <IconSeparatorVertical class="text-secondary fill-accent stroke-[1.5]" />
+          </li>
+          <li
+            class={cn(
+              "px-1 h-full flex flex-row justify-center items-center hover:bg-accent transition-colors cursor-pointer",
+              isAutoCrop() && "bg-accent",
+            )}
+            title="crop page margins"
+            onClick={async () => await handleSetAutoCrop()}
+          >
+            <IconCrop class="text-secondary fill-accent stroke-[1.5]" />
+          </li>
+          <li
+            class="px-1 h-full flex flex-row justify-center items-center hover:bg-accent transition-colors cursor-pointer"
+            title="reading direction"
+            onClick={async () => await handleSetDirection()}
+          >
+            <Show when={direction() !== "Vertical"}
+              fallback={
+                <IconArrowsVertical class="text-secondary fill-accent stroke-[1.5]" />
+              }
+            >
+              <Show when={direction() === "LeftToRight"}
+                fallback={
+                  <IconTextDirectionRtl class="text-secondary fill-accent stroke-[1.5]" />
+                }
+              >
+                <IconTextDirectionLtr class="text-secondary fill-accent stroke-[1.5]" />
+              </Show>
+            </Show>
+          </li>
         </div>
 
         <li class="px-1 h-full flex flex-row justify-center items-center hover:bg-accent transition-colors cursor-pointer">
@@ -1,15 +1,25 @@
 import { useParams } from "@solidjs/router";
-import { Accessor, createEffect, createResource, createSignal, ErrorBoundary, For, JSX, Show } from "solid-js";
+import { Accessor, createEffect, createResource, createSignal, ErrorBoundary, For, JSX, on, onCleanup, onMount, Show } from "solid-js";
 import get_os_folder_by_path from "../../tauri-cmds/mpv/get_os_folder_by_path";
 import ReaderNavbar from "./reader-nav";
 import get_user_by_id from "../../tauri-cmds/get_user_by_id";
 import { get_panels } from "../../tauri-cmds/get_panels";
-import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import get_spread_layout from "../../tauri-cmds/get_spread_layout";
+import { get_reader_panels } from "../../tauri-cmds/split/get_reader_panels";
+import { get_folder_split_spreads, set_folder_split_spreads } from "../../tauri-cmds/split/split_spreads";
+import { get_auto_crop, get_folder_auto_crop, set_folder_auto_crop } from "../../tauri-cmds/crop/auto_crop";
+import { get_webtoon_tiles } from "../../tauri-cmds/webtoon/get_webtoon_tiles";
+import { get_folder_reading_profile_overrides, get_reading_profile, set_folder_reading_profile } from "../../tauri-cmds/profile/reading_profile";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { IconChevronLeft, IconChevronRight, IconChevronsLeft, IconChevronsRight } from "@tabler/icons-solidjs";
 import update_os_folders from "../../tauri-cmds/os_folders/update_os_folders";
-import { MangaPanel, OsFolder } from "../../models";
+import { FitMode, Margins, MangaPanel, OsFolder, ReaderPanel, ReadingDirection, WebtoonTile } from "../../models";
 import { Transition } from "solid-transition-group";
 import { cn } from "../../libs/cn";
+import { readerPageWidth, shelfPageSrc } from "../../libs/shelf";
+import prefetch_panels from "../../tauri-cmds/prefetch/prefetch_panels";
+import cancel_prefetch from "../../tauri-cmds/prefetch/cancel_prefetch";
 import upsert_read_os_dir from "../../tauri-cmds/handle_stale_folder";
 import ErrorAlert from "../../main-components/error-alert";
 import { platform } from "@tauri-apps/plugin-os";
@@ -23,7 +33,15 @@ export default function MangaReader() {
   const [parentFolder] = createResource(() => (currentMangaFolder() ? currentMangaFolder()?.parent_path : null), get_os_folder_by_path);
   const [user] = createResource(() => (currentMangaFolder() ? currentMangaFolder()?.user_id : null), get_user_by_id);
 
-  const [panels, { refetch: refetchPanels }] = createResource(() => (currentMangaFolder() ? currentMangaFolder()?.path : null), get_panels);
+  const [panels, { refetch: refetchPanels }] = createResource(() => (currentMangaFolder() ? currentMangaFolder()?.path : null), get_reader_panels);
+  const [splitSpreads, { refetch: refetchSplitSpreads }] = createResource(folderPath, get_folder_split_spreads);
+  const [folderCrop, { refetch: refetchFolderCrop }] = createResource(folderPath, get_folder_auto_crop);
+  // refetched with the panels, the crop is worked out from the pages the reader shows
+  const [autoCrop, { refetch: refetchAutoCrop }] = createResource(
+    () => (currentMangaFolder() && user() && panels() ? [currentMangaFolder()!.path, user()!.id] as const : null),
+    ([path, userId]) => get_auto_crop(path, userId),
+  );
+  const [profile, { mutate: setProfile }] = createResource(folderPath, get_reading_profile);
   const [panelIndex, setPanelIndex] = createSignal<number>(0);
   const [isDoublePanels, setIsDoublePanels] = createSignal(false);
   const [isfullyHydrated, setIsFullyHydrated] = createSignal(false);
@@ -32,13 +50,15 @@ export default function MangaReader() {
   // hydrates stale folders
   createEffect(async () => {
     if (!isfullyHydrated() && folderPath() && currentMangaFolder() && user() && panels()) {
+      // split halves aren't files in the folder, so the stale check needs the panels as they are on disk
+      const sourcePanels = await get_panels(currentMangaFolder()?.path!);
       const is_refetch = await
         upsert_read_os_dir(
           currentMangaFolder()?.path!,
           currentMangaFolder()?.parent_path,
           user()!,
           undefined,
-          panels()!
+          sourcePanels ?? undefined
         );
       if (is_refetch) {
         await refetchPanels();
@@ -55,7 +75,9 @@ export default function MangaReader() {
 
       // find the panel index based on last read panel path
       for (let i = 0; i < panels()!.length; i++) {
-        if (panels()![i].path === currentMangaFolder()?.last_read_panel?.path) {
+        const lastRead = currentMangaFolder()?.last_read_panel?.path;
+        // progress is saved as the source panel, so a split page opens on its first half
+        if (panels()![i].path === lastRead || panels()![i].source_path === lastRead) {
           setPanelIndex(i);
           //handleUpdateFolders();
           break;
@@ -67,23 +89,93 @@ export default function MangaReader() {
     }
   });
 
+  // decodes the next pages in the background so page turns don't wait on big scans
+  createEffect(() => {
+    const panel = panels()?.[panelIndex()];
+    // webtoons are read as tiles, which the webview loads on its own
+    if (hasInitialized() && panel && profile()?.direction !== "Vertical") {
+      prefetch_panels(panel, readerPageWidth(isDoublePanels()), autoCrop());
+    }
+  });
+
+  // pages added or removed on disk show up without reopening the folder, the index stays on the same page
+  let unlisten: UnlistenFn | null;
+
+  onMount(async () => {
+    unlisten = await listen<string[]>("library_changed", async (event) => {
+      if (hasInitialized() && event.payload.includes(folderPath())) {
+        const currentPanel = panels()?.[panelIndex()];
+        await refetchPanels();
+        const index = panels()?.findIndex((p) => p.path === currentPanel?.path) ?? -1;
+        setPanelIndex(Math.max(Math.min(index === -1 ? panelIndex() : index, panels()!.length - 1), 0));
+      }
+    });
+  });
+
+  onCleanup(() => {
+    if (unlisten) {
+      unlisten();
+    }
+  });
+
+  // stops prefetching the previous folder when moving to another one, and when leaving the reader
+  createEffect(on(folderPath, (_, prevFolder) => {
+    if (prevFolder) {
+      cancel_prefetch(prevFolder);
+    }
+  }));
+  onCleanup(() => {
+    cancel_prefetch(folderPath());
+  });
+
+  const isLeftToRight = () => profile()?.direction === "LeftToRight";
+  const isVertical = () => profile()?.direction === "Vertical";
+  const fitMode = () => profile()?.fit_mode ?? "Screen";
+  const pageGap = () => `${profile()?.page_gap ?? 0}px`;
+
+  // webtoons are read as tiles cut from the strip of pages, progress still goes to the pages
+  const [tiles] = createResource(() => (isVertical() && panels() ? folderPath() : null), get_webtoon_tiles);
+
+  // double panel mode pairs pages the way the backend lays them out, wide pages and an offset cover get a spread of their own
+  const [spreads] = createResource(
+    () => (isDoublePanels() && panels() && profile() ? [folderPath(), profile()!.offset_cover] as const : null),
+    ([path, offsetCover]) => get_spread_layout(path, offsetCover),
+  );
+  const spreadIndex = () => spreads()?.findIndex((s) => s.first === panelIndex() || s.second === panelIndex()) ?? -1;
+  const currentSpread = () => (isDoublePanels() ? spreads()?.[spreadIndex()] : undefined);
+  const isLastPage = () =>
+    currentSpread() ? spreadIndex() === spreads()!.length - 1 : panelIndex() === panels()?.length! - 1;
+
+  // the indexes of the pages on screen, only double panel mode has a second one
+  const shownIndexes = () => ({
+    first: currentSpread()?.first ?? panelIndex(),
+    second: currentSpread()?.second ?? null,
+  });
+
   const CURRENT_PANELS = () => ({
-    // for right-to-left reading, the "first" (right) panel is the current index
-    first: panels()?.[panelIndex()],
-    // the "second" (left) panel is the next index in double panel mode
-    second: isDoublePanels() ? panels()?.[panelIndex() + 1] : null,
+    // for right-to-left reading, the "first" (right) panel is the first page of the spread
+    first: panels()?.[shownIndexes().first],
+    // the "second" (left) panel is the spread's other page in double panel mode
+    second: shownIndexes().second !== null ? panels()?.[shownIndexes().second!] : null,
   });
 
+  // split halves only live in the cache, progress points at the page they were cut from
+  const progressPanel = ({ source_path, ...panel }: ReaderPanel): MangaPanel =>
+    source_path
+      ? { ...panel, path: source_path, title: source_path.split(/[\\/]/).pop()! }
+      : panel;
+
   const handleUpdateFolders = async () => {
     if (currentMangaFolder.state === "ready"
       && panelIndex() !== undefined
       && panels.state === "ready"
       && user.state === "ready"
     ) {
+      const lastReadPanel = progressPanel(panels()![panelIndex()]);
       let newFolder = structuredClone(currentMangaFolder()!);
-      newFolder.last_read_panel = panels()![panelIndex()];
+      newFolder.last_read_panel = lastReadPanel;
 
-      if (panelIndex() === panels()!.length - 1) {
+      if (isLastPage()) {
         newFolder.is_read = true;
       } else {
         newFolder.is_read = false;
@@ -93,12 +185,12 @@ export default function MangaReader() {
 
       if (parentFolder.state === "ready" && parentFolder()) {
         let newParentFolder = structuredClone(parentFolder()!);
-        newParentFolder.last_read_panel = panels()![panelIndex()];
+        newParentFolder.last_read_panel = lastReadPanel;
         // this should be moved to rust because it calls rust functions anyway
         if (newParentFolder.parent_path) {
           const SUPER_PARENT = await getOutermostParentFolder(newParentFolder);
           if (SUPER_PARENT.path !== newParentFolder.path) {
-            SUPER_PARENT.last_read_panel = panels()![panelIndex()];
+            SUPER_PARENT.last_read_panel = lastReadPanel;
             foldersToUpdate.push(newParentFolder);
           }
         }
@@ -134,6 +226,53 @@ export default function MangaReader() {
     }
   }
 
+  // a folder without its own setting follows the user's default
+  const isAutoCrop = () => folderCrop()?.is_enabled ?? !!autoCrop();
+
+  async function handleSetAutoCrop() {
+    if (currentMangaFolder() && user()) {
+      await set_folder_auto_crop(currentMangaFolder()!.path, user()!.id, !isAutoCrop());
+      await refetchFolderCrop();
+      await refetchAutoCrop();
+    }
+  }
+
+  // right-to-left, then left-to-right, then a vertical strip
+  async function handleSetDirection() {
+    if (currentMangaFolder() && user() && profile()) {
+      const DIRECTIONS: ReadingDirection[] = ["RightToLeft", "LeftToRight", "Vertical"];
+      const direction = DIRECTIONS[(DIRECTIONS.indexOf(profile()!.direction) + 1) % DIRECTIONS.length];
+      const overrides = (await get_folder_reading_profile_overrides(currentMangaFolder()!.path))?.profile;
+      setProfile(await set_folder_reading_profile(currentMangaFolder()!.path, user()!.id, {
+        direction,
+        fit_mode: overrides?.fit_mode ?? null,
+        page_gap: overrides?.page_gap ?? null,
+        offset_cover: overrides?.offset_cover ?? null,
+      }));
+
+      // split halves are ordered by the reading direction
+      if (splitSpreads()?.is_enabled) {
+        await set_folder_split_spreads(currentMangaFolder()!.path, user()!.id, true);
+        await refetchSplitSpreads();
+        await refetchPanels();
+      }
+    }
+  }
+
+  async function handleSetSplitSpreads() {
+    if (currentMangaFolder() && user()) {
+      const currentPanel = panels()?.[panelIndex()];
+      await set_folder_split_spreads(currentMangaFolder()!.path, user()!.id, !splitSpreads()?.is_enabled);
+      await refetchSplitSpreads();
+      await refetchPanels();
+
+      // stay on the same page, halves point at the panel they were split from
+      const sourcePath = currentPanel?.source_path ?? currentPanel?.path;
+      const index = panels()?.findIndex((p) => (p.source_path ?? p.path) === sourcePath) ?? -1;
+      setPanelIndex(Math.max(Math.min(index, panels()!.length - 1), 0));
+    }
+  }
+
   const handleSetFirstPanel = async () => {
     if (panelIndex() === 0) {
       let prev: OsFolder | null = null;
@@ -158,8 +297,7 @@ export default function MangaReader() {
       return;
     }
 
-    let panelLen = panels()!.length - 1;
-    if (panelIndex() === panelLen) {
+    if (isLastPage()) {
       let next: OsFolder | null = null;
       try {
         next = await invoke("get_next_folder", { parentPath: parentFolder()?.path, currentFolder: currentMangaFolder() });
@@ -171,14 +309,19 @@ export default function MangaReader() {
         setHasInitialized(false);
       }
     } else {
-      setPanelIndex(panelLen);
+      setPanelIndex(currentSpread() ? spreads()![spreads()!.length - 1].first : panels()!.length - 1);
       await handleUpdateFolders();
     }
   };
 
+  // moves a whole spread, or two pages until the layout has loaded
   const handleNextPanel = async () => {
     if (panels()) {
-      setPanelIndex(Math.min(panelIndex() + 2, panels()?.length! - 1));
+      if (currentSpread()) {
+        setPanelIndex(spreads()![Math.min(spreadIndex() + 1, spreads()!.length - 1)].first);
+      } else {
+        setPanelIndex(Math.min(panelIndex() + 2, panels()?.length! - 1));
+      }
       await handleUpdateFolders();
     }
   };
@@ -192,8 +335,12 @@ export default function MangaReader() {
 
   const handlePrevPanel = async () => {
     if (panels()) {
-      // Decrease by 2, but ensure it doesn't go below 0
-      setPanelIndex(Math.max(panelIndex() - 2, 0));
+      if (currentSpread()) {
+        setPanelIndex(spreads()![Math.max(spreadIndex() - 1, 0)].first);
+      } else {
+        // Decrease by 2, but ensure it doesn't go below 0
+        setPanelIndex(Math.max(panelIndex() - 2, 0));
+      }
     }
     await handleUpdateFolders();
   };
@@ -218,6 +365,12 @@ export default function MangaReader() {
         setPanelIndex={setPanelIndex}
         setCurrentMangaFolder={setCurrentMangaFolder}
         handleSetDoublePanels={handleSetDoublePanels}
+        isSplitSpreads={() => splitSpreads()?.is_enabled ?? false}
+        handleSetSplitSpreads={handleSetSplitSpreads}
+        isAutoCrop={isAutoCrop}
+        handleSetAutoCrop={handleSetAutoCrop}
+        direction={() => profile()?.direction ?? "RightToLeft"}
+        handleSetDirection={handleSetDirection}
         handleSetFirstPanel={handleSetFirstPanel}
         handleSetLastPanel={handleSetLastPanel}
         handlePrevSinglePanel={handlePrevSinglePanel}
@@ -238,74 +391,91 @@ export default function MangaReader() {
           }}
         >
           <Show when={currentMangaFolder.state === "ready"}>
-            <Show when={panels.state === "ready" && user.state === "ready"}>
-              <div class="h-full w-full flex justify-center items-center pt-0.5">
-                <NavigationButtons
-                  isLastPanel={() => panelIndex() === panels()?.length! - 1}
-                  isFirstPanel={() => panelIndex() === 0}
-                  isDoublePanels={isDoublePanels}
-                  handleNextPanel={handleNextPanel}
-                  handlePrevPanel={handlePrevPanel}
-                  handleNextSinglePanel={handleNextSinglePanel}
-                  handlePrevSinglePanel={handlePrevSinglePanel}
-                  handleSetLastPanel={handleSetLastPanel}
-                  handleSetFirstPanel={handleSetFirstPanel}
+            <Show when={panels.state === "ready" && user.state === "ready" && profile.state === "ready"}>
+              <Show when={isVertical() && tiles.state === "ready"}>
+                <VerticalStrip
+                  tiles={tiles()!}
+                  panels={panels()!}
+                  panelIndex={panelIndex}
+                  setPanelIndex={(i) => {
+                    setPanelIndex(i);
+                    handleUpdateFolders();
+                  }}
                 />
-                <div class={cn("relative flex justify-center items-center ", isDoublePanels() && "flex-row-reverse")}>
+              </Show>
+              <Show when={!isVertical()}>
+                <div class={cn("h-full w-full flex justify-center items-center pt-0.5", fitMode() !== "Screen" && "overflow-auto")}>
+                  <NavigationButtons
+                    isLastPanel={isLastPage}
+                    isFirstPanel={() => panelIndex() === 0}
+                    isDoublePanels={isDoublePanels}
+                    handleNextPanel={handleNextPanel}
+                    handlePrevPanel={handlePrevPanel}
+                    handleNextSinglePanel={handleNextSinglePanel}
+                    handlePrevSinglePanel={handlePrevSinglePanel}
+                    handleSetLastPanel={handleSetLastPanel}
+                    handleSetFirstPanel={handleSetFirstPanel}
+                    isLeftToRight={isLeftToRight}
+                  />
                   <div
-                    class="w-full h-fit pb-20 text-lg flex justify-center z-50 opacity-0 group hover:opacity-100 transition-opacity duration-300 absolute"
-                    style={{
-                      top: "0",
-                      left: "50%",
-                      transform: "translateX(-50%)",
-                    }}
+                    class={cn("relative flex justify-center items-center ", isDoublePanels() && !isLeftToRight() && "flex-row-reverse")}
+                    style={{ gap: isDoublePanels() ? pageGap() : undefined }}
                   >
-                    <h1
-                      class="p-1 leading-none truncated w-fit text-center text-nowrap text-secondary bg-primary
-										group-hover:shadow-md group-hover:mix-blend-luminosity
-										select-none px-3 h-fit pb-1.5 rounded-b-sm
-											font-semibold will-change-auto z-50"
+                    <div
+                      class="w-full h-fit pb-20 text-lg flex justify-center z-50 opacity-0 group hover:opacity-100 transition-opacity duration-300 absolute"
+                      style={{
+                        top: "0",
+                        left: "50%",
+                        transform: "translateX(-50%)",
+                      }}
                     >
-                      {currentMangaFolder()?.title}
-                    </h1>
-                  </div>
+                      <h1
+                        class="p-1 leading-none truncated w-fit text-center text-nowrap text-secondary bg-primary
+  										group-hover:shadow-md group-hover:mix-blend-luminosity
+  										select-none px-3 h-fit pb-1.5 rounded-b-sm
+  											font-semibold will-change-auto z-50"
+                      >
+                        {currentMangaFolder()?.title}
+                      </h1>
+                    </div>
 
-                  <For each={panels()}>
-                    {(panel, i) => {
-                      return (
-                        <Show when={i() >= panelIndex() - 10 && i() <= panelIndex() + 10}>
-                          < RenderPanel panel={panel} isDoublePanels={isDoublePanels} panelIndex={panelIndex} i={i} />
-                        </Show>
-                      );
-                    }}
-                  </For>
+                    <For each={panels()}>
+                      {(panel, i) => {
+                        return (
+                          <Show when={i() >= panelIndex() - 10 && i() <= panelIndex() + 10}>
+                            < RenderPanel panel={panel} isDoublePanels={isDoublePanels} fitMode={fitMode} crop={autoCrop} shown={shownIndexes} i={i} />
+                          </Show>
+                        );
+                      }}
+                    </For>
 
-                  <div
-                    class={cn(
-                      "w-full h-fit pt-20 flex justify-between items-end text-lg z-50 opacity-0 group hover:opacity-100 transition-opacity duration-300 absolute",
-                      !isDoublePanels && "justify-center",
-                    )}
-                    style={{
-                      bottom: "0",
-                      left: "50%",
-                      transform: "translateX(-50%)",
-                    }}
-                  >
-                    <Show when={isDoublePanels()}>
-                      <p class="bg-primary text-sm select-none text-muted font-medium py-1 px-2">{CURRENT_PANELS().second?.title}</p>
-                    </Show>
-                    <h1
+                    <div
                       class={cn(
-                        "w-fit flex flex-col text-center text-nowrap text-secondary bg-primary group-hover:shadow-md group-hover:mix-blend-luminosity select-none px-3 h-fit pb-0.5 rounded-t-sm font-semibold will-change-auto z-50",
-                        !isDoublePanels() && "rounded-none",
+                        "w-full h-fit pt-20 flex justify-between items-end text-lg z-50 opacity-0 group hover:opacity-100 transition-opacity duration-300 absolute",
+                        !isDoublePanels && "justify-center",
                       )}
+                      style={{
+                        bottom: "0",
+                        left: "50%",
+                        transform: "translateX(-50%)",
+                      }}
                     >
-                      {panelIndex() + 1}/{panels()?.length!}
-                    </h1>
-                    <p class="bg-primary text-sm select-none text-muted font-medium py-1 px-2">{CURRENT_PANELS().first?.title}</p>
+                      <Show when={isDoublePanels()}>
+                        <p class="bg-primary text-sm select-none text-muted font-medium py-1 px-2">{CURRENT_PANELS().second?.title}</p>
+                      </Show>
+                      <h1
+                        class={cn(
+                          "w-fit flex flex-col text-center text-nowrap text-secondary bg-primary group-hover:shadow-md group-hover:mix-blend-luminosity select-none px-3 h-fit pb-0.5 rounded-t-sm font-semibold will-change-auto z-50",
+                          !isDoublePanels() && "rounded-none",
+                        )}
+                      >
+                        {panelIndex() + 1}/{panels()?.length!}
+                      </h1>
+                      <p class="bg-primary text-sm select-none text-muted font-medium py-1 px-2">{CURRENT_PANELS().first?.title}</p>
+                    </div>
                   </div>
                 </div>
-              </div>
+              </Show>
             </Show>
           </Show>
         </Transition>
@@ -324,8 +494,10 @@ interface NavigationButtonsProps {
   handlePrevSinglePanel: () => Promise<void>;
   handleSetLastPanel: () => Promise<void>;
   handleSetFirstPanel: () => Promise<void>;
+  isLeftToRight: () => boolean;
 }
 
+// the next page is on the left for right-to-left reading, the buttons swap sides for left-to-right
 const NavigationButtons = ({
   isLastPanel,
   isFirstPanel,
@@ -336,12 +508,14 @@ const NavigationButtons = ({
   handlePrevSinglePanel,
   handleSetLastPanel,
   handleSetFirstPanel,
+  isLeftToRight,
 }: NavigationButtonsProps) => (
   <>
     {/* Left Button */}
     <div
       class={cn(
-        "h-full w-1/4 z-20 absolute left-0 flex items-center cursor-pointer justify-center hover:bg-primary/15 transition-all opacity-0 hover:opacity-30 will-change-auto",
+        "h-full w-1/4 z-20 absolute flex items-center cursor-pointer justify-center hover:bg-primary/15 transition-all opacity-0 hover:opacity-30 will-change-auto",
+        isLeftToRight() ? "right-0 rotate-180" : "left-0",
       )}
       onClick={async () => {
         if (isLastPanel()) {
@@ -363,7 +537,8 @@ const NavigationButtons = ({
     {/* Right Button */}
     <div
       class={cn(
-        "h-full w-1/4 z-20 absolute right-0 flex items-center cursor-pointer justify-center hover:bg-primary/15 transition-all opacity-0 hover:opacity-30 will-change-auto",
+        "h-full w-1/4 z-20 absolute flex items-center cursor-pointer justify-center hover:bg-primary/15 transition-all opacity-0 hover:opacity-30 will-change-auto",
+        isLeftToRight() ? "left-0 rotate-180" : "right-0",
         isFirstPanel() && "hover:opacity-70",
       )}
       onClick={async () => {
@@ -389,16 +564,20 @@ const NavigationButtons = ({
 function RenderPanel({
   panel,
   isDoublePanels,
+  fitMode,
+  crop,
   i,
-  panelIndex,
+  shown,
 }: {
   panel: MangaPanel;
   isDoublePanels: Accessor<boolean>;
+  fitMode: Accessor<FitMode>;
+  crop: Accessor<Margins | null | undefined>;
   i: Accessor<number>;
-  panelIndex: Accessor<number>;
+  shown: Accessor<{ first: number; second: number | null }>;
 }) {
-  const isCurrent = () => panelIndex() === i();
-  const isNext = () => isDoublePanels() && panelIndex() === i() + 1;
+  const isCurrent = () => shown().first === i();
+  const isNext = () => isDoublePanels() && shown().second === i();
 
   // Styles for positioning
   let style = {
@@ -408,19 +587,21 @@ function RenderPanel({
     transform: "translate(-50%, -50%)", // Adjust for exact centering
   } satisfies JSX.CSSProperties;
 
+  const src = () => shelfPageSrc(panel.path, readerPageWidth(isDoublePanels()), crop());
+
   return (
     <img
-      src={convertFileSrc(panel.path)}
+      src={src()}
       alt={panel.title || "Panel"}
       decoding="async"
       class={cn(
-        "select-none bg-black will-change-auto object-contain max-h-[calc(100vh-37px)]",
+        "select-none bg-black will-change-auto object-contain",
         isCurrent() || isNext() ? "opacity-100 z-20" : "opacity-[0.002]",
-        isDoublePanels() ? "max-w-[calc((100vw-10px)/2)]" : "max-w-[calc((100vw-10px))]",
+        fitClass(fitMode(), isDoublePanels()),
       )}
       style={isCurrent() || isNext() ? { position: "relative" } : style}
       onError={(e) => {
-        console.error(`Image failed to load: ${convertFileSrc(panel.path)}`);
+        console.error(`Image failed to load: ${src()}`);
         e.preventDefault();
         e.currentTarget.onerror = null; // Prevent infinite loop
         e.currentTarget.src = img_err; // Replace with fallback image
@@ -428,3 +609,73 @@ function RenderPanel({
     />
   );
 }
+
+function fitClass(fitMode: FitMode, isDoublePanels: boolean) {
+  switch (fitMode) {
+    case "Width":
+      return isDoublePanels ? "w-[calc((100vw-10px)/2)] h-auto" : "w-[calc((100vw-10px))] h-auto";
+    case "Height":
+      return "h-[calc(100vh-37px)] w-auto max-w-none";
+    case "Original":
+      return "max-w-none max-h-none";
+    default:
+      return cn("max-h-[calc(100vh-37px)]", isDoublePanels ? "max-w-[calc((100vw-10px)/2)]" : "max-w-[calc((100vw-10px))]");
+  }
+}
+
+// a webtoon as one long strip of tiles, the page the tile closest to the top of the screen shows most of is the current one
+function VerticalStrip({
+  tiles,
+  panels,
+  panelIndex,
+  setPanelIndex,
+}: {
+  tiles: WebtoonTile[];
+  panels: MangaPanel[];
+  panelIndex: Accessor<number>;
+  setPanelIndex: (i: number) => void;
+}) {
+  let strip: HTMLDivElement | undefined;
+  const elements: HTMLImageElement[] = [];
+
+  onMount(() => {
+    const current = panels[panelIndex()]?.path;
+    const first = tiles.findIndex((t) => t.panel_path === current || t.sources.some((s) => s.path === current));
+    elements[first]?.scrollIntoView({ block: "start" });
+
+    const observer = new IntersectionObserver((entries) => {
+      for (const entry of entries) {
+        const tile = tiles[elements.indexOf(entry.target as HTMLImageElement)];
+        const i = panels.findIndex((p) => p.path === tile?.panel_path);
+        if (entry.isIntersecting && i !== -1 && i !== panelIndex()) {
+          setPanelIndex(i);
+        }
+      }
+    }, { root: strip, rootMargin: "0px 0px -90% 0px" });
+    elements.forEach((el) => observer.observe(el));
+    onCleanup(() => observer.disconnect());
+  });
+
+  return (
+    <div ref={strip} class="h-full w-full overflow-y-auto flex flex-col items-center">
+      <For each={tiles}>
+        {(tile, i) => (
+          <img
+            ref={(el) => (elements[i()] = el)}
+            src={shelfPageSrc(tile.path, readerPageWidth(false))}
+            width={tile.width}
+            height={tile.height}
+            alt={panels.find((p) => p.path === tile.panel_path)?.title || "Panel"}
+            loading="lazy"
+            decoding="async"
+            class="select-none w-full max-w-[calc((100vw-10px))] h-auto"
+            onError={(e) => {
+              e.currentTarget.onerror = null;
+              e.currentTarget.src = img_err;
+            }}
+          />
+        )}
+      </For>
+    </div>
+  );
+}
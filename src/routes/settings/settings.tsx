@@ -27,6 +27,7 @@ import { IconAdjustments, IconUserFilled } from "@tabler/icons-solidjs";
 import IconMpv from "../../main-components/icons/icon-mpv";
 import ProfileTabSection from "./tab-sections/profile";
 import SettingsErrorCard from "./tab-sections/error-card";
+import ClockSection from "./tab-sections/clock";
 import NavBar from "../../main-components/navbar";
 import { Transition } from "solid-transition-group";
 
@@ -78,6 +79,9 @@ export default function Settings() {
             </h1>
           </header>
           {errorMessage && <SettingsErrorCard message={errorMessage} />}
+          <Show when={user()}>
+            {(user) => <ClockSection user={user()} />}
+          </Show>
           {/* <Tabs */}
           {/*   defaultValue="profile" */}
           {/*   class="w-full" */}
@@ -0,0 +1,49 @@
+import { Button } from "../../../components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "../../../components/ui/card";
+import { IconClock } from "@tabler/icons-solidjs";
+import { For } from "solid-js";
+import { ClockFormat, UserType } from "../../../models";
+import { clockFormat, formatTime, setClockFormat } from "../../../libs/datetime";
+import update_user from "../../../tauri-cmds/update-user";
+import { cn } from "../../../libs/cn";
+
+const CLOCK_FORMATS: { value: ClockFormat; label: string }[] = [
+  { value: "Locale", label: "System" },
+  { value: "TwelveHour", label: "12-hour" },
+  { value: "TwentyFourHour", label: "24-hour" },
+];
+
+export default function ClockSection({ user }: { user: UserType }) {
+  // dates always follow the locale, only the hours change
+  const handleSetClock = async (clock: ClockFormat) => {
+    await update_user({ ...user, settings: { ...user.settings, clock } });
+    setClockFormat(clock);
+  };
+
+  return (
+    <Card class="w-full">
+      <CardHeader>
+        <CardTitle>
+          Clock
+          <IconClock class="h-4 w-4" />
+        </CardTitle>
+        <CardDescription>
+          How times are shown, it's {formatTime(Date.now())} right now.
+        </CardDescription>
+      </CardHeader>
+      <CardContent class="flex flex-row gap-2">
+        <For each={CLOCK_FORMATS}>
+          {(format) => (
+            <Button
+              variant="outline"
+              class={cn("text-xs", clockFormat() === format.value && "bg-primary text-secondary")}
+              onClick={() => handleSetClock(format.value)}
+            >
+              {format.label}
+            </Button>
+          )}
+        </For>
+      </CardContent>
+    </Card>
+  );
+}
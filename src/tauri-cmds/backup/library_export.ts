@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+import { ImportStrategy, ImportSummary } from "../../models";
+
+export async function export_library(path: string) {
+  await invoke("export_library", { path });
+}
+
+export async function import_library(path: string, strategy: ImportStrategy) {
+  return await invoke("import_library", { path, strategy }) as ImportSummary;
+}
@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+import { DatabaseSnapshot } from "../../models";
+
+export async function create_database_snapshot() {
+  return await invoke("create_database_snapshot") as DatabaseSnapshot;
+}
+
+// newest first
+export async function get_database_snapshots() {
+  return await invoke("get_database_snapshots") as DatabaseSnapshot[];
+}
+
+// the app restarts to load the snapshot, the current database is snapshotted first
+export async function restore_database_snapshot(path: string) {
+  await invoke("restore_database_snapshot", { path });
+}
@@ -0,0 +1,5 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export default async function cancel_scan(path?: string) {
+  return await invoke("cancel_scan", { path }) as boolean;
+}
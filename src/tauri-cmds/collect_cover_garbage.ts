@@ -0,0 +1,5 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export default async function collect_cover_garbage() {
+  return await invoke("collect_cover_garbage") as number;
+}
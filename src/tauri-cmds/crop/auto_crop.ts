@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+import { FolderCrop, Margins } from "../../models";
+
+// the margins shared by the folder's pages, `null` if it isn't cropped
+export async function get_auto_crop(folderPath: string, userId: string) {
+  return await invoke("get_auto_crop", { folderPath, userId }) as Margins | null;
+}
+
+export async function get_folder_auto_crop(folderPath: string) {
+  return await invoke("get_folder_auto_crop", { folderPath }) as FolderCrop | null;
+}
+
+// `undefined` makes the folder follow the user's default again
+export async function set_folder_auto_crop(folderPath: string, userId: string, isEnabled?: boolean) {
+  await invoke("set_folder_auto_crop", { folderPath, userId, isEnabled });
+}
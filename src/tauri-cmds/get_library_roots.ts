@@ -0,0 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
+import { LibraryRoot } from "../models";
+
+export default async function get_library_roots(userId: string) {
+  return await invoke("get_library_roots", { userId }) as LibraryRoot[];
+}
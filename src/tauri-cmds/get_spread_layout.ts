@@ -0,0 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
+import { Spread } from "../models";
+
+export default async function get_spread_layout(parentPath: string, offsetCover?: boolean) {
+  return await invoke("get_spread_layout", { parentPath, offsetCover }) as Spread[];
+}
@@ -1,9 +1,9 @@
 import { invoke } from "@tauri-apps/api/core";
 import { OsFolder } from "../../models";
 
-const read_os_folder_dir = async (path: string, userId: string, coverImgPath?: string, updateDatetime?: string[]) => {
+const read_os_folder_dir = async (path: string, userId: string, coverImgPath?: string, updatedAt?: number) => {
   try {
-    const osFolders: [OsFolder, OsFolder[]] = await invoke("read_os_folder_dir", { path, userId, coverImgPath, updateDatetime });
+    const osFolders: [OsFolder, OsFolder[]] = await invoke("read_os_folder_dir", { path, userId, coverImgPath, updatedAt });
     return osFolders;
   } catch (error) {
     console.error("read_os_folder_dir", error);
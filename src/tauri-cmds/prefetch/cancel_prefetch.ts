@@ -0,0 +1,5 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export default async function cancel_prefetch(folder?: string) {
+  return await invoke("cancel_prefetch", { folder }) as boolean;
+}
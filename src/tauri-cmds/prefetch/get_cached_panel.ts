@@ -0,0 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
+import { Margins } from "../../models";
+
+export default async function get_cached_panel(path: string, width?: number, crop?: Margins | null) {
+  return await invoke("get_cached_panel", { path, width, crop }) as ArrayBuffer;
+}
@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api/core";
+import { Margins, MangaPanel } from "../../models";
+
+// decodes the panels around `panel` in the background, so `shelf://` can serve them from memory
+export default async function prefetch_panels(panel: MangaPanel, width?: number, crop?: Margins | null, ahead?: number, behind?: number) {
+  try {
+    return await invoke("prefetch_panels", { panel, width, crop, ahead, behind }) as number;
+  } catch (error) {
+    console.error("prefetch_panels", error);
+    return 0;
+  }
+}
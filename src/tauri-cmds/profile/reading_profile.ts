@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+import { FolderReadingProfile, ReadingProfile, ReadingProfileOverrides } from "../../models";
+
+// the folder's profile with everything it inherits filled in
+export async function get_reading_profile(folderPath: string) {
+  return await invoke("get_reading_profile", { folderPath }) as ReadingProfile;
+}
+
+export async function get_folder_reading_profile_overrides(folderPath: string) {
+  return await invoke("get_folder_reading_profile_overrides", { folderPath }) as FolderReadingProfile | null;
+}
+
+// returns the folder's profile after the change, `null` makes it inherit everything again
+export async function set_folder_reading_profile(folderPath: string, userId: string, profile: ReadingProfileOverrides | null) {
+  return await invoke("set_folder_reading_profile", { folderPath, userId, profile }) as ReadingProfile;
+}
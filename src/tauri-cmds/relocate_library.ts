@@ -0,0 +1,5 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export default async function relocate_library(oldPrefix: string, newPrefix: string) {
+  return await invoke("relocate_library", { oldPrefix, newPrefix }) as number;
+}
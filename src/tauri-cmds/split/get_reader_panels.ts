@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api/core";
+import { ReaderPanel } from "../../models";
+
+// the folder's panels, with wide panels replaced by their halves if the folder splits spreads
+export async function get_reader_panels(parentPath: string) {
+  try {
+    return await invoke("get_reader_panels", { parentPath }) as ReaderPanel[];
+  } catch (error) {
+    console.error("get_reader_panels", error);
+    return null;
+  }
+}
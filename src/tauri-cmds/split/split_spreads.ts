@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+import { SplitSpreads } from "../../models";
+
+export async function get_folder_split_spreads(folderPath: string) {
+  return await invoke("get_folder_split_spreads", { folderPath }) as SplitSpreads | null;
+}
+
+export async function set_folder_split_spreads(folderPath: string, userId: string, isEnabled: boolean, rightToLeft?: boolean) {
+  await invoke("set_folder_split_spreads", { folderPath, userId, isEnabled, rightToLeft });
+}
@@ -0,0 +1,11 @@
+import { invoke } from "@tauri-apps/api/core";
+import { CoverVariant, CoverVariants } from "../../models";
+
+export async function get_cover_variants(coverPaths: string[]) {
+  return await invoke("get_cover_variants", { coverPaths }) as CoverVariants[];
+}
+
+// the smallest thumbnail that is at least as wide as the space it's shown in
+export function pick_cover_variant(variants: CoverVariant[], width: number) {
+  return variants.find((v) => v.width >= width) ?? variants[variants.length - 1];
+}
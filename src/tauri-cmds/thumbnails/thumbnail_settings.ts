@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+import { ThumbnailSettings } from "../../models";
+
+export async function get_thumbnail_settings(userId: string) {
+  return await invoke("get_thumbnail_settings", { userId }) as ThumbnailSettings;
+}
+
+export async function update_thumbnail_settings(settings: ThumbnailSettings) {
+  await invoke("update_thumbnail_settings", { settings });
+}
@@ -0,0 +1,7 @@
+import { invoke } from "@tauri-apps/api/core";
+import { WebtoonTile } from "../../models";
+
+// the folder's panels cut into screen sized tiles, the first call for a folder cuts them
+export async function get_webtoon_tiles(folderPath: string) {
+  return await invoke("get_webtoon_tiles", { folderPath }) as WebtoonTile[];
+}